@@ -3,13 +3,32 @@ use std::hint::black_box;
 use rust_ffi_example::{
     compress_rust_string, decompress_rust_data,
     compress_rust_string_lz4, decompress_rust_data_lz4,
-    compress_rust_string_zstd, decompress_rust_data_zstd
+    compress_rust_string_zstd, compress_rust_string_zstd_level, decompress_rust_data_zstd,
+    compress_rust_bytes, decompress_rust_data_bytes, Codec
 };
 
 fn generate_test_data(size: usize, pattern: &str) -> String {
     pattern.repeat(size / pattern.len() + 1)[..size].to_string()
 }
 
+// Deterministic xorshift64 output, so runs are comparable without a rand dependency
+fn generate_random_bytes(size: usize) -> Vec<u8> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+// Slowly increasing little-endian u32s, like an index or timestamp column
+fn generate_le_integers(size: usize) -> Vec<u8> {
+    (0u32..).flat_map(|i| (i * 3).to_le_bytes()).take(size).collect()
+}
+
 fn bench_compression_by_size(c: &mut Criterion) {
     let sizes = vec![100, 1000, 10000, 100000];
     let test_pattern = "This is a test string that should compress well with zlib. ";
@@ -192,7 +211,10 @@ criterion_group!(
     bench_zstd_decompression_by_pattern,
     bench_zstd_decompression_small_strings,
     bench_zstd_decompression_edge_cases,
-    bench_zstd_decompression_real_world_data
+    bench_zstd_decompression_real_world_data,
+    bench_zstd_fast_vs_default_level,
+    // Bytes API Benchmarks
+    bench_bytes_by_size
 );
 criterion_main!(benches);
 
@@ -731,4 +753,75 @@ fn bench_zstd_decompression_real_world_data(c: &mut Criterion) {
         });
     }
     group.finish();
-}
\ No newline at end of file
+}
+
+// Negative (fast) levels should compress faster than the default level 3, at some cost in
+// ratio; the ratio side is checked by test_zstd_fast_level_larger_than_level_3.
+fn bench_zstd_fast_vs_default_level(c: &mut Criterion) {
+    // Log-like lines, the same shape as the unit test's input
+    const REQUESTS: [&str; 3] = ["GET /index.html HTTP/1.1 200", "POST /api/v1/items HTTP/1.1 201", "GET /static/app.js HTTP/1.1 304"];
+    let mut state = 1u32;
+    let data: String = (0..100_000)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            format!("{} {} ms={}\n", REQUESTS[(state >> 16) as usize % 3], (state >> 8) % 100_000, (state >> 20) % 500)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("zstd_fast_vs_default_level");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for level in [-5, 3] {
+        group.bench_with_input(
+            BenchmarkId::new("zstd_compress_level", level),
+            &data,
+            |b, data| {
+                b.iter(|| {
+                    compress_rust_string_zstd_level(black_box(data), level).unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+
+// --- Bytes (non-UTF-8) API Benchmarks ---
+// Binary buffers go through compress_rust_bytes/decompress_rust_data_bytes for every codec.
+
+fn bench_bytes_by_size(c: &mut Criterion) {
+    let sizes = vec![100, 1000, 10000, 100000];
+    let mut group = c.benchmark_group("bytes_by_size");
+
+    for size in sizes {
+        group.throughput(Throughput::Bytes(size as u64));
+        let inputs = vec![
+            ("random", generate_random_bytes(size)),
+            ("le_integers", generate_le_integers(size)),
+        ];
+        for (data_name, data) in inputs {
+            for codec in Codec::ALL {
+                let compressed_data = compress_rust_bytes(&data, codec).expect("Bytes compression failed during benchmark setup");
+
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{}_compress_{}", codec.name(), data_name), size),
+                    &data,
+                    |b, data| {
+                        b.iter(|| {
+                            compress_rust_bytes(black_box(data), codec).unwrap()
+                        });
+                    },
+                );
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{}_decompress_{}", codec.name(), data_name), size),
+                    &compressed_data,
+                    |b, data| {
+                        b.iter(|| {
+                            decompress_rust_data_bytes(black_box(data), codec).unwrap()
+                        });
+                    },
+                );
+            }
+        }
+    }
+    group.finish();
+}
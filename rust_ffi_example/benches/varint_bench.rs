@@ -156,7 +156,7 @@ fn bench_varint_edge_cases(c: &mut Criterion) {
     
     for (case_name, value) in edge_cases {
         // Encode benchmark
-        group.bench_function(&format!("encode_{}", case_name), |b| {
+        group.bench_function(format!("encode_{}", case_name), |b| {
             b.iter(|| {
                 encode_varint_rust(black_box(value)).unwrap()
             });
@@ -164,14 +164,14 @@ fn bench_varint_edge_cases(c: &mut Criterion) {
         
         // Decode benchmark
         let encoded = encode_varint_rust(value).unwrap();
-        group.bench_function(&format!("decode_{}", case_name), |b| {
+        group.bench_function(format!("decode_{}", case_name), |b| {
             b.iter(|| {
                 decode_varint_rust(black_box(&encoded)).unwrap()
             });
         });
         
         // Round-trip benchmark
-        group.bench_function(&format!("roundtrip_{}", case_name), |b| {
+        group.bench_function(format!("roundtrip_{}", case_name), |b| {
             b.iter(|| {
                 let encoded = encode_varint_rust(black_box(value)).unwrap();
                 let (decoded, _) = decode_varint_rust(black_box(&encoded)).unwrap();
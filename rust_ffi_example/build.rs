@@ -68,22 +68,28 @@ fn main() {
                 or ensure pkg-config can locate it.");
     }
 
-    // Find and configure lz4
-    if !find_and_add_library(&mut build, "liblz4", "lz4", "lz4.h") {
-        // Panic with instructions if lz4 isn't found
-        panic!("lz4 library or headers not found. \
-                Please install the lz4 development package (e.g., 'liblz4-dev' on Debian/Ubuntu, \
-                'lz4-devel' on Fedora/CentOS, or 'lz4' via Homebrew/MacPorts) \
-                or ensure pkg-config can locate it.");
+    // Find and configure lz4, only when the `lz4` feature is enabled
+    if cfg!(feature = "lz4") {
+        if !find_and_add_library(&mut build, "liblz4", "lz4", "lz4.h") {
+            // Panic with instructions if lz4 isn't found
+            panic!("lz4 library or headers not found. \
+                    Please install the lz4 development package (e.g., 'liblz4-dev' on Debian/Ubuntu, \
+                    'lz4-devel' on Fedora/CentOS, or 'lz4' via Homebrew/MacPorts), \
+                    ensure pkg-config can locate it, or disable the `lz4` feature.");
+        }
+        build.define("HAVE_LZ4", "1");
     }
 
-    // Find and configure zstd
-    if !find_and_add_library(&mut build, "libzstd", "zstd", "zstd.h") {
-        // Panic with instructions if zstd isn't found
-        panic!("zstd library or headers not found. \
-                Please install the zstd development package (e.g., 'libzstd-dev' on Debian/Ubuntu, \
-                'zstd-devel' on Fedora/CentOS, or 'zstd' via Homebrew/MacPorts) \
-                or ensure pkg-config can locate it.");
+    // Find and configure zstd, only when the `zstd` feature is enabled
+    if cfg!(feature = "zstd") {
+        if !find_and_add_library(&mut build, "libzstd", "zstd", "zstd.h") {
+            // Panic with instructions if zstd isn't found
+            panic!("zstd library or headers not found. \
+                    Please install the zstd development package (e.g., 'libzstd-dev' on Debian/Ubuntu, \
+                    'zstd-devel' on Fedora/CentOS, or 'zstd' via Homebrew/MacPorts), \
+                    ensure pkg-config can locate it, or disable the `zstd` feature.");
+        }
+        build.define("HAVE_ZSTD", "1");
     }
 
     // Ensure Cargo reruns this script if the C file changes
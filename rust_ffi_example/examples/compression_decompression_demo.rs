@@ -30,7 +30,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(compressed_zlib) => {
             let compress_duration_zlib = start_compress_zlib.elapsed();
             let mut zlib_compression_ratio = 0.0;
-            if file_contents.len() > 0 {
+            if !file_contents.is_empty() {
                 zlib_compression_ratio = (compressed_zlib.len() as f64 / file_contents.len() as f64) * 100.0;
             }
             print!("ZLIB: Original size: {}, Compressed size: {}, Compression ratio: {:.2}%, Time to compress: {:.2?}", 
@@ -67,7 +67,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(compressed_lz4) => {
             let compress_duration_lz4 = start_compress_lz4.elapsed();
             let mut lz4_compression_ratio = 0.0;
-            if file_contents.len() > 0 {
+            if !file_contents.is_empty() {
                 lz4_compression_ratio = (compressed_lz4.len() as f64 / file_contents.len() as f64) * 100.0;
             }
             print!("LZ4: Original size: {}, Compressed size: {}, Compression ratio: {:.2}%, Time to compress: {:.2?}", 
@@ -104,7 +104,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(compressed_zstd) => {
             let compress_duration_zstd = start_compress_zstd.elapsed();
             let mut zstd_compression_ratio = 0.0;
-            if file_contents.len() > 0 {
+            if !file_contents.is_empty() {
                 zstd_compression_ratio = (compressed_zstd.len() as f64 / file_contents.len() as f64) * 100.0;
             }
             print!("ZSTD: Original size: {}, Compressed size: {}, Compression ratio: {:.2}%, Time to compress: {:.2?}", 
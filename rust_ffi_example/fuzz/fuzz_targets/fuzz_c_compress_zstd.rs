@@ -1,7 +1,7 @@
 #![no_main]
 use libfuzzer_sys::fuzz_target;
 use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
-use rust_ffi_example::{compress_rust_string_zstd, decompress_rust_data_zstd};
+use rust_ffi_example::{compress_rust_string_zstd, decompress_rust_data_zstd, CompressionError};
 
 #[derive(Debug, Clone)]
 struct FuzzInput {
@@ -43,7 +43,7 @@ fuzz_target!(|input: FuzzInput| {
             // The fuzzer will continue exploring other inputs.
             if original_data.contains('\0') {
                 // Expected error for strings with null bytes.
-                assert_eq!(e, "Failed to create CString, input might contain null bytes");
+                assert_eq!(e, CompressionError::NullByte);
             } else {
                 // Unexpected compression error
                 // It's useful to know if compression fails for other reasons.
@@ -0,0 +1,106 @@
+#![no_main]
+//! Differential fuzzing of the streaming zstd paths against the one-shot API.
+//!
+//! * `CompressWriter`, fed in fuzzer-sized chunks, must produce a frame that the one-shot
+//!   decoders turn back into the input: `decompress_zstd_stream` directly, and
+//!   `decompress_rust_data_bytes` once the varint length header is prepended.
+//! * A one-shot `compress_rust_bytes` blob, read in fuzzer-sized chunks by
+//!   `transcode_stream`'s incremental decoder, must come back as the input.
+use std::io::{self, Read, Write};
+
+use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured};
+use libfuzzer_sys::fuzz_target;
+use rust_ffi_example::{
+    compress_rust_bytes, decompress_rust_data_bytes, decompress_zstd_stream, encode_varint_rust, transcode_stream, Codec,
+    CompressWriter,
+};
+
+#[derive(Debug, Clone)]
+struct FuzzInput {
+    /// Sizes of successive writes/reads, reused cyclically; 0 is treated as 1.
+    chunk_sizes: Vec<u16>,
+    /// Flush the writer after every chunk, forcing a block boundary there.
+    flush_between: bool,
+    level: i8,
+    data: Vec<u8>,
+}
+
+impl<'a> Arbitrary<'a> for FuzzInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self, libfuzzer_sys::arbitrary::Error> {
+        Ok(FuzzInput {
+            chunk_sizes: Vec::<u16>::arbitrary(u)?,
+            flush_between: bool::arbitrary(u)?,
+            level: i8::arbitrary(u)?,
+            data: Vec::<u8>::arbitrary(u)?,
+        })
+    }
+}
+
+/// Cycles through the fuzzer's chunk sizes, or yields the whole input if there are none.
+fn chunk_len(sizes: &[u16], index: usize, remaining: usize) -> usize {
+    match sizes.get(index % sizes.len().max(1)) {
+        Some(&size) => usize::from(size).clamp(1, remaining.max(1)),
+        None => remaining,
+    }
+}
+
+/// Hands out the input in fuzzer-controlled pieces, like a socket or pipe would.
+struct ChunkedReader<'a> {
+    data: &'a [u8],
+    sizes: &'a [u16],
+    calls: usize,
+}
+
+impl Read for ChunkedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = chunk_len(self.sizes, self.calls, self.data.len()).min(buf.len()).min(self.data.len());
+        self.calls += 1;
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+fuzz_target!(|input: FuzzInput| {
+    let data = &input.data;
+    if data.is_empty() {
+        return;
+    }
+    let zstd = Codec::from_name("zstd").expect("fuzz targets build with the zstd feature");
+
+    // Streaming compression -> one-shot decompression
+    let level = i32::from(input.level).clamp(-5, 19);
+    let mut writer = CompressWriter::new(Vec::new(), level).expect("level is within zstd's range");
+    let mut rest = &data[..];
+    let mut index = 0;
+    while !rest.is_empty() {
+        let (chunk, tail) = rest.split_at(chunk_len(&input.chunk_sizes, index, rest.len()));
+        writer.write_all(chunk).expect("writing into a Vec cannot fail");
+        if input.flush_between {
+            writer.flush().expect("flushing into a Vec cannot fail");
+        }
+        rest = tail;
+        index += 1;
+    }
+    let frame = writer.finish().expect("finishing into a Vec cannot fail");
+
+    assert_eq!(decompress_zstd_stream(&frame).as_deref(), Ok(&data[..]), "Streamed frame should decode one-shot");
+    let mut with_header = encode_varint_rust(data.len() as u64).expect("length fits a varint");
+    with_header.extend_from_slice(&frame);
+    assert_eq!(
+        decompress_rust_data_bytes(&with_header, zstd).as_deref(),
+        Ok(&data[..]),
+        "Streamed frame behind a varint header should decode like a one-shot blob"
+    );
+
+    // One-shot compression -> streaming decompression
+    let blob = compress_rust_bytes(data, zstd).expect("non-empty input should compress");
+    let mut reader = ChunkedReader { data: &blob, sizes: &input.chunk_sizes, calls: 0 };
+    let mut zlib = Vec::new();
+    assert_eq!(transcode_stream(&mut reader, &mut zlib, Codec::Zlib), Ok(zstd), "One-shot blob should be detected as zstd");
+    assert_eq!(
+        decompress_rust_data_bytes(&zlib, Codec::Zlib).as_deref(),
+        Ok(&data[..]),
+        "Incremental decoding should reproduce the one-shot input"
+    );
+});
@@ -15,11 +15,20 @@ typedef struct {
     unsigned long length;
 } DecompressedData;
 
+// Error codes reported in DecompressedData.length when buffer is NULL.
+#define DECOMPRESS_ERR_GENERIC 0
+// The varint header disagrees with the size the payload actually decodes to.
+#define DECOMPRESS_ERR_HEADER_MISMATCH 1
+
 #ifdef __cplusplus
 extern "C" {
 #endif
 
 // FFI function declarations
+//
+// Alignment: every function accepts input pointers with no alignment beyond a single
+// byte. Code paths that want wider aligned loads must check alignment themselves and
+// fall back to byte-wise processing otherwise.
 
 /**
  * Compresses a string using the default algorithm.
@@ -27,6 +36,13 @@ extern "C" {
  */
 CompressedData compress_string(const char* input, unsigned long input_len);
 
+/**
+ * Compresses a string using zlib at the given level, 0 (store) to 9 (best), or -1
+ * for zlib's default. Out-of-range levels return a NULL buffer.
+ * The caller is responsible for freeing the returned CompressedData using free_compressed_data.
+ */
+CompressedData compress_string_level(const char* input, unsigned long input_len, int level);
+
 /**
  * Frees the memory allocated for CompressedData.
  */
@@ -35,6 +51,7 @@ void free_compressed_data(CompressedData data);
 /**
  * Decompresses data using the default algorithm.
  * The caller is responsible for freeing the returned DecompressedData using free_decompressed_data.
+ * On failure buffer is NULL and length holds one of the DECOMPRESS_ERR_* codes.
  */
 DecompressedData decompress_data(const char* input, unsigned long input_len);
 
@@ -43,6 +60,20 @@ DecompressedData decompress_data(const char* input, unsigned long input_len);
  */
 void free_decompressed_data(DecompressedData data);
 
+/**
+ * Compresses a string as raw deflate: the zlib stream without its 2-byte header and
+ * 4-byte Adler-32 trailer. Output layout is [varint original length][raw deflate data].
+ * The caller is responsible for freeing the returned CompressedData using free_compressed_data.
+ */
+CompressedData compress_string_deflate_raw(const char* input, unsigned long input_len);
+
+/**
+ * Decompresses data produced by compress_string_deflate_raw.
+ * The caller is responsible for freeing the returned DecompressedData using free_decompressed_data.
+ * On failure buffer is NULL and length holds one of the DECOMPRESS_ERR_* codes.
+ */
+DecompressedData decompress_data_deflate_raw(const char* input, unsigned long input_len);
+
 /**
  * Compresses a string using the LZ4 algorithm.
  * The caller is responsible for freeing the returned CompressedData using free_compressed_data.
@@ -61,6 +92,24 @@ DecompressedData decompress_data_lz4(const char* input, unsigned long input_len)
  */
 CompressedData compress_string_zstd(const char* input, unsigned long input_len);
 
+/**
+ * Compresses a string using the Zstd algorithm at the given level.
+ * Levels range from zstd_min_compression_level() (negative, fastest) to
+ * zstd_max_compression_level(); out-of-range levels return a NULL buffer.
+ * The caller is responsible for freeing the returned CompressedData using free_compressed_data.
+ */
+CompressedData compress_string_zstd_level(const char* input, unsigned long input_len, int level);
+
+/**
+ * Returns the lowest (fastest, negative) level accepted by compress_string_zstd_level.
+ */
+int zstd_min_compression_level(void);
+
+/**
+ * Returns the highest level accepted by compress_string_zstd_level.
+ */
+int zstd_max_compression_level(void);
+
 /**
  * Decompresses data compressed with Zstd.
  * The caller is responsible for freeing the returned DecompressedData using free_decompressed_data.
@@ -83,6 +132,87 @@ int32_t encode_varint(unsigned long value, char* buffer);
  */
 int32_t decode_varint(const char* buffer, int32_t max_bytes, unsigned long* value);
 
+/**
+ * Encodes an unsigned long value MSB-first (Git OFS_DELTA style): the most significant
+ * 7-bit group comes first, and each continuation adds one to the remaining value.
+ * Not interchangeable with encode_varint. buffer must hold at least 10 bytes.
+ * Returns the number of bytes written.
+ */
+int32_t encode_varint_msb(unsigned long value, char* buffer);
+
+/**
+ * Decodes an MSB-first VarInt written by encode_varint_msb.
+ * Returns the number of bytes read, or a negative value if the VarInt is truncated or overflows.
+ */
+int32_t decode_varint_msb(const char* buffer, int32_t max_bytes, unsigned long* value);
+
+/**
+ * Same as decode_varint, but decodes up to 8 bytes with a single word load.
+ * The fast path is only used for 8-byte aligned buffers with at least 8 readable
+ * bytes; unaligned or short input is decoded by the scalar path, so any byte
+ * pointer is accepted.
+ */
+int32_t decode_varint_swar(const char* buffer, int32_t max_bytes, unsigned long* value);
+
+/**
+ * Decompresses only bytes [start, start + len) of the original data, stopping as soon as
+ * they are available. The range is clamped to the original length from the header.
+ * The caller is responsible for freeing the returned DecompressedData using free_decompressed_data.
+ */
+DecompressedData decompress_data_range(const char* input, unsigned long input_len, unsigned long start, unsigned long len);
+DecompressedData decompress_data_range_lz4(const char* input, unsigned long input_len, unsigned long start, unsigned long len);
+DecompressedData decompress_data_range_zstd(const char* input, unsigned long input_len, unsigned long start, unsigned long len);
+
+/**
+ * Streaming zstd compression over an opaque stream handle.
+ * zstd_stream_create returns NULL if the level is out of range.
+ * zstd_stream_set_target_block_size bounds each compressed block (ZSTD_c_targetCBlockSize);
+ * returns 0 on success, -1 if the size is rejected.
+ * zstd_stream_compress mode: 0 = continue, 1 = flush, 2 = end frame. The output is a
+ * plain zstd frame without the varint length header; free it with free_compressed_data.
+ */
+void* zstd_stream_create(int level);
+int zstd_stream_set_target_block_size(void* stream, unsigned long bytes);
+CompressedData zstd_stream_compress(void* stream, const char* input, unsigned long input_len, int mode);
+void zstd_stream_free(void* stream);
+
+/**
+ * Decompresses a complete zstd frame produced by the streaming compressor.
+ * The caller is responsible for freeing the returned DecompressedData using free_decompressed_data.
+ */
+DecompressedData decompress_data_zstd_stream(const char* input, unsigned long input_len);
+
+/**
+ * Returns the CRC-32 (IEEE polynomial, as used by gzip) of the buffer.
+ */
+unsigned long content_crc32(const char* input, unsigned long input_len);
+
+/**
+ * Returns the worst-case size of compress_string's output for input_len bytes.
+ */
+unsigned long compress_string_bound(unsigned long input_len);
+
+/**
+ * Compresses into a caller-provided buffer instead of allocating one.
+ * Returns the bytes written, or 0 on failure or if output_cap is below compress_string_bound.
+ */
+unsigned long compress_string_into(const char* input, unsigned long input_len, char* output, unsigned long output_cap);
+
+/**
+ * Incremental decompression of a bare zlib stream (zlib_dstream_*) or a single zstd
+ * frame (zstd_dstream_*). Each decompress call produces at most max_output bytes,
+ * reports the input bytes consumed and sets *finished at the end of the stream.
+ * Returns a NULL buffer on corrupt input.
+ */
+void* zlib_dstream_create(void);
+DecompressedData zlib_dstream_decompress(void* stream, const char* input, unsigned long input_len,
+                                         unsigned long max_output, unsigned long* consumed, int* finished);
+void zlib_dstream_free(void* stream);
+void* zstd_dstream_create(void);
+DecompressedData zstd_dstream_decompress(void* stream, const char* input, unsigned long input_len,
+                                         unsigned long max_output, unsigned long* consumed, int* finished);
+void zstd_dstream_free(void* stream);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
@@ -1,24 +1,78 @@
-use rust_ffi_example::{compress_rust_string, decompress_rust_data, encode_varint_rust, decode_varint_rust};
+use rust_ffi_example::{encode_varint_rust, decode_varint_rust, compress_rust_bytes, compress_rust_bytes_level, decompress_rust_data_bytes, Codec, CompressionError};
 use std::env;
 use std::fs;
 use std::io::{self, Read};
 
 fn print_usage(program_name: &str) {
     println!("Usage:");
-    println!("  {} compress [text]              - Compress text (or from stdin)", program_name);
-    println!("  {} decompress <file>            - Decompress binary file", program_name);
+    println!("  {} compress [--algo A] [--level N] [text] - Compress text (or from stdin)", program_name);
+    println!("  {} compress --binary [--algo A] [--level N] [file] - Compress raw bytes of a file (or stdin)", program_name);
+    println!("  {} decompress [--algo A] [--binary] <file> - Decompress binary file", program_name);
     println!("  {} encode-varint <number>         - Encode a u64 number into varint format (output as hex)", program_name);
     println!("  {} decode-varint <hex_bytes>      - Decode varint hex bytes into a u64 number", program_name);
     println!("  echo 'text' | {} compress       - Compress from stdin", program_name);
-    println!("");
+    println!();
+    println!("Algorithms (--algo): zlib (default), lz4, zstd");
+    println!("  compress writes compressed_output.<ext> with ext zlib, lz4 or zst.");
+    println!("  decompress infers the algorithm from the file extension when --algo is omitted.");
+    println!("Levels (--level): zlib 0-9, zstd negative (fast) up to 22; lz4 has no levels.");
+    println!("Binary mode (--binary): input may contain null bytes or non-UTF-8 data;");
+    println!("  decompress writes the raw bytes to decompressed_output.bin without printing them.");
+    println!();
     println!("Examples:");
     println!("  {} compress \"Hello, world!\"", program_name);
-    println!("  {} decompress compressed_output.bin", program_name);
+    println!("  {} compress --algo zstd \"Hello, world!\"", program_name);
+    println!("  {} compress --algo zstd --level 19 \"Hello, world!\"", program_name);
+    println!("  {} decompress compressed_output.zst", program_name);
+    println!("  {} compress --binary --algo lz4 image.png", program_name);
     println!("  {} encode-varint 12345", program_name);
     println!("  {} decode-varint c96101", program_name);
     println!("  echo \"Hello from stdin\" | {} compress", program_name);
 }
 
+/// Flags shared by the compress and decompress subcommands.
+struct Flags {
+    algo: Option<Codec>,
+    level: Option<i32>,
+    binary: bool,
+    positional: Vec<String>,
+}
+
+/// Splits `args` into the `--algo`/`--level`/`--binary` flags (if any) and the remaining positional arguments.
+fn parse_flags(args: &[String]) -> Result<Flags, String> {
+    let mut flags = Flags { algo: None, level: None, binary: false, positional: Vec::new() };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--algo" {
+            let name = iter.next().ok_or("--algo requires a value")?;
+            flags.algo = Some(Codec::from_name(name).ok_or_else(|| format!("Unknown algorithm '{}'", name))?);
+        } else if arg == "--level" {
+            let value = iter.next().ok_or("--level requires a value")?;
+            flags.level = Some(value.parse().map_err(|_| format!("Invalid level '{}'", value))?);
+        } else if arg == "--binary" {
+            flags.binary = true;
+        } else {
+            flags.positional.push(arg.clone());
+        }
+    }
+    Ok(flags)
+}
+
+/// Checks `level` against the range `codec` supports.
+fn validate_level(codec: Codec, level: i32) -> Result<(), String> {
+    match codec.level_range() {
+        None => Err(format!("{} does not support --level", codec.name())),
+        Some(range) if !range.contains(&level) => Err(format!(
+            "Level {} is out of range for {} ({} to {})",
+            level,
+            codec.name(),
+            range.start(),
+            range.end()
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
     
@@ -31,15 +85,47 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     match operation.as_str() {
         "compress" => {
-            let input_data = if args.len() > 2 {
+            let Flags { algo, level, binary, positional } = match parse_flags(&args[2..]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    print_usage(&args[0]);
+                    std::process::exit(1);
+                }
+            };
+            let codec = algo.unwrap_or(Codec::Zlib);
+            if let Some(level) = level {
+                if let Err(e) = validate_level(codec, level) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+
+            let input_data: Vec<u8> = if binary {
+                // In binary mode the positional argument is a file to read as raw bytes
+                match positional.first() {
+                    Some(path) => match fs::read(path) {
+                        Ok(data) => data,
+                        Err(e) => {
+                            eprintln!("Error reading file '{}': {}", path, e);
+                            std::process::exit(1);
+                        }
+                    },
+                    None => {
+                        let mut buffer = Vec::new();
+                        io::stdin().read_to_end(&mut buffer)?;
+                        buffer
+                    }
+                }
+            } else if let Some(text) = positional.first() {
                 // Use command line argument as input
-                args[2].clone()
+                text.clone().into_bytes()
             } else {
                 // Read from stdin
                 println!("Reading from stdin... (press Ctrl+D when done)");
                 let mut buffer = String::new();
                 io::stdin().read_to_string(&mut buffer)?;
-                buffer
+                buffer.into_bytes()
             };
 
             if input_data.is_empty() {
@@ -47,10 +133,28 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 return Ok(());
             }
 
+            println!("Algorithm: {}", codec.name());
+            match level {
+                Some(level) => println!("Level: {}", level),
+                None => println!("Level: default"),
+            }
             println!("Original data length: {} bytes", input_data.len());
 
             // Compress the data
-            match compress_rust_string(&input_data) {
+            let compressed = if binary {
+                match level {
+                    Some(level) => compress_rust_bytes_level(&input_data, codec, level),
+                    None => compress_rust_bytes(&input_data, codec),
+                }
+            } else {
+                // Text input came from a String, so this borrows without replacing anything
+                let text = String::from_utf8_lossy(&input_data);
+                match level {
+                    Some(level) => codec.compress_level(&text, level),
+                    None => codec.compress(&text),
+                }
+            };
+            match compressed {
                 Ok(compressed_data) => {
                     println!("Compressed data length: {} bytes", compressed_data.len());
                     println!(
@@ -68,8 +172,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     println!("Compressed data (first 16 bytes as hex): {}", hex_preview);
                     
                     // Write compressed data to file
-                    let output_file = "compressed_output.bin";
-                    fs::write(output_file, &compressed_data)?;
+                    // The extension records the codec so decompress can pick it automatically
+                    let output_file = format!("compressed_output.{}", codec.file_extension());
+                    fs::write(&output_file, &compressed_data)?;
                     println!("Compressed data written to: {}", output_file);
                     println!("To decompress: {} decompress {}", args[0], output_file);
                 }
@@ -80,13 +185,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         "decompress" => {
-            if args.len() < 3 {
-                eprintln!("Error: Decompress requires a file path.");
-                print_usage(&args[0]);
+            let Flags { algo, level, binary, positional } = match parse_flags(&args[2..]) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    print_usage(&args[0]);
+                    std::process::exit(1);
+                }
+            };
+            if level.is_some() {
+                eprintln!("Error: --level only applies to compress.");
                 std::process::exit(1);
             }
+            let file_path = match positional.first() {
+                Some(path) => path,
+                None => {
+                    eprintln!("Error: Decompress requires a file path.");
+                    print_usage(&args[0]);
+                    std::process::exit(1);
+                }
+            };
 
-            let file_path = &args[2];
+            // An explicit --algo wins; otherwise go by extension, falling back to zlib
+            // for files without a recognised extension (e.g. the old compressed_output.bin).
+            let codec = algo.or_else(|| Codec::from_path(file_path)).unwrap_or(Codec::Zlib);
 
             // Read compressed data from file
             let compressed_data = match fs::read(file_path) {
@@ -97,17 +219,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             };
 
+            println!("Algorithm: {}", codec.name());
             println!("Compressed data length: {} bytes", compressed_data.len());
 
             // Decompress the data (original size is read automatically from header)
-            match decompress_rust_data(&compressed_data) {
-                Ok(decompressed_string) => {
-                    println!("Decompressed data length: {} bytes", decompressed_string.len());
-                    println!("Decompressed data: \"{}\"", decompressed_string);
-                    
-                    // Write decompressed data to file
-                    let output_file = "decompressed_output.txt";
-                    fs::write(output_file, &decompressed_string)?;
+            match decompress_rust_data_bytes(&compressed_data, codec) {
+                Ok(decompressed_data) => {
+                    println!("Decompressed data length: {} bytes", decompressed_data.len());
+
+                    let output_file = if binary {
+                        "decompressed_output.bin"
+                    } else {
+                        match std::str::from_utf8(&decompressed_data) {
+                            Ok(text) => println!("Decompressed data: \"{}\"", text),
+                            Err(_) => {
+                                eprintln!("Decompression failed: {}", CompressionError::InvalidUtf8(codec));
+                                eprintln!("Use --binary to write the raw bytes.");
+                                std::process::exit(1);
+                            }
+                        }
+                        "decompressed_output.txt"
+                    };
+
+                    // Write the raw decompressed bytes to file
+                    fs::write(output_file, &decompressed_data)?;
                     println!("Decompressed data written to: {}", output_file);
                 }
                 Err(e) => {
@@ -0,0 +1,339 @@
+//! Byte-oriented wrappers for binary data.
+//!
+//! The `&str` wrappers go through `CString` and reject interior null bytes, and the
+//! decompressors insist on UTF-8 output. The C functions themselves take an explicit
+//! length, so binary buffers can be passed straight through with the same
+//! `[varint original length][compressed data]` layout.
+
+use std::borrow::Cow;
+use std::os::raw::c_char;
+use std::{slice, str};
+
+use libc::c_ulong;
+
+use crate::codec::Codec;
+use crate::error::CompressionError;
+use crate::{
+    compress_string, compress_string_level, decompress_data, decompress_data_range, decompress_error, free_compressed_data,
+    free_decompressed_data, EMPTY_FRAME,
+};
+#[cfg(feature = "lz4")]
+use crate::{compress_string_lz4, decompress_data_lz4, decompress_data_range_lz4};
+#[cfg(feature = "zstd")]
+use crate::{compress_string_zstd, compress_string_zstd_level, decompress_data_range_zstd, decompress_data_zstd};
+
+/// Compresses arbitrary bytes with `codec`.
+///
+/// The output is byte-for-byte the same as the `&str` wrapper would produce for the
+/// same input, so `Codec::decompress` can read it back if the input was UTF-8.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` containing the compressed data.
+/// * `Err(CompressionError::InputTooLarge)` if `data` is over `Codec::max_input_size`.
+/// * `Err(CompressionError)` if the C library fails to compress the input.
+pub fn compress_rust_bytes(data: &[u8], codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    codec.check_input_size(data.len())?;
+    let input_ptr = data.as_ptr() as *const c_char;
+    let input_len = data.len() as c_ulong;
+
+    let compressed_c_data = unsafe {
+        match codec {
+            Codec::Zlib => compress_string(input_ptr, input_len),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => compress_string_lz4(input_ptr, input_len),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => compress_string_zstd(input_ptr, input_len),
+        }
+    };
+
+    if compressed_c_data.buffer.is_null() {
+        return Err(CompressionError::CompressionFailed(codec));
+    }
+
+    let rust_vec = unsafe {
+        let slice = slice::from_raw_parts(compressed_c_data.buffer as *const u8, compressed_c_data.length as usize);
+        let vec = slice.to_vec();
+        free_compressed_data(compressed_c_data);
+        vec
+    };
+
+    Ok(rust_vec)
+}
+
+/// Compresses arbitrary bytes with `codec` at an explicit level, as `Codec::compress_level`
+/// does for strings.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` containing the compressed data.
+/// * `Err(CompressionError::InvalidLevel)` if `level` is outside `codec.level_range()`.
+/// * `Err(CompressionError)` if the C library fails to compress the input.
+pub fn compress_rust_bytes_level(data: &[u8], codec: Codec, level: i32) -> Result<Vec<u8>, CompressionError> {
+    if !codec.level_range().is_some_and(|range| range.contains(&level)) {
+        return Err(CompressionError::InvalidLevel { codec, level });
+    }
+    codec.check_input_size(data.len())?;
+    let input_ptr = data.as_ptr() as *const c_char;
+    let input_len = data.len() as c_ulong;
+
+    let compressed_c_data = unsafe {
+        match codec {
+            Codec::Zlib => compress_string_level(input_ptr, input_len, level),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => unreachable!("LZ4 has no level range"),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => compress_string_zstd_level(input_ptr, input_len, level),
+        }
+    };
+
+    if compressed_c_data.buffer.is_null() {
+        return Err(CompressionError::CompressionFailed(codec));
+    }
+
+    let rust_vec = unsafe {
+        let slice = slice::from_raw_parts(compressed_c_data.buffer as *const u8, compressed_c_data.length as usize);
+        let vec = slice.to_vec();
+        free_compressed_data(compressed_c_data);
+        vec
+    };
+
+    Ok(rust_vec)
+}
+
+/// Decompresses data produced by `compress_rust_bytes` (or any `&str` compressor) without
+/// requiring the output to be UTF-8.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the original bytes.
+/// * `Err(CompressionError)` if the input is malformed or fails to decompress.
+pub fn decompress_rust_data_bytes(compressed_data: &[u8], codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    let mut out = Vec::new();
+    decompress_into(compressed_data, codec, &mut out)?;
+    Ok(out)
+}
+
+/// Decompresses `data` into `buf` and returns the text borrowed from it.
+///
+/// `buf` is cleared first and keeps its capacity, so reusing one buffer across calls
+/// avoids allocating a new `String` for every message. The result is always
+/// `Cow::Borrowed`; call `into_owned` to keep it past the next reuse of `buf`.
+///
+/// # Returns
+/// * `Ok(Cow<str>)` borrowing the decompressed text from `buf`.
+/// * `Err(CompressionError)` if decompression fails or the output is not UTF-8;
+///   `buf` then holds no meaningful data.
+pub fn decompress_cow<'a>(data: &[u8], buf: &'a mut Vec<u8>, codec: Codec) -> Result<Cow<'a, str>, CompressionError> {
+    decompress_into(data, codec, buf)?;
+    str::from_utf8(buf).map(Cow::Borrowed).map_err(|_| CompressionError::InvalidUtf8(codec))
+}
+
+/// Replaces the contents of `out` with the decompressed bytes of `compressed_data`.
+fn decompress_into(compressed_data: &[u8], codec: Codec, out: &mut Vec<u8>) -> Result<(), CompressionError> {
+    out.clear();
+    decompress_append(compressed_data, codec, out).map(|_| ())
+}
+
+/// Decompresses `compressed_data` and appends the bytes to `out`, keeping what is
+/// already there.
+///
+/// Decompressing a series of frames into one growing `Vec` this way avoids an
+/// allocation per frame and the copy of concatenating them afterwards.
+///
+/// # Returns
+/// * `Ok(usize)` with the number of bytes appended.
+/// * `Err(CompressionError)` if the input is malformed or fails to decompress;
+///   `out` is left unchanged.
+pub fn decompress_append(compressed_data: &[u8], codec: Codec, out: &mut Vec<u8>) -> Result<usize, CompressionError> {
+    if compressed_data.is_empty() {
+        return Err(CompressionError::EmptyInput);
+    }
+    if compressed_data.len() < 2 && compressed_data != EMPTY_FRAME {
+        return Err(CompressionError::InputTooSmall(codec));
+    }
+
+    let input_ptr = compressed_data.as_ptr() as *const c_char;
+    let input_len = compressed_data.len() as c_ulong;
+
+    let decompressed_c_data = unsafe {
+        match codec {
+            Codec::Zlib => decompress_data(input_ptr, input_len),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => decompress_data_lz4(input_ptr, input_len),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => decompress_data_zstd(input_ptr, input_len),
+        }
+    };
+
+    if decompressed_c_data.buffer.is_null() {
+        return Err(decompress_error(&decompressed_c_data, codec));
+    }
+
+    let appended = decompressed_c_data.length as usize;
+    unsafe {
+        let slice = slice::from_raw_parts(decompressed_c_data.buffer as *const u8, appended);
+        out.extend_from_slice(slice);
+        free_decompressed_data(decompressed_c_data);
+    }
+
+    Ok(appended)
+}
+
+/// Decompresses only bytes `start..start + len` of the original data.
+///
+/// zlib and zstd stream through the payload, discarding output before `start` and
+/// stopping once `len` bytes are collected, so the rest of the blob is never decoded.
+/// LZ4 blocks cannot be entered midway; LZ4 decodes the prefix up to `start + len`
+/// and stops there. The range is clamped to the original length in the header, so a
+/// range running past the end returns the bytes that exist (possibly none).
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the requested bytes.
+/// * `Err(CompressionError)` if the input is malformed or ends before the range does.
+pub fn decompress_range(data: &[u8], codec: Codec, start: usize, len: usize) -> Result<Vec<u8>, CompressionError> {
+    if data.is_empty() {
+        return Err(CompressionError::EmptyInput);
+    }
+    if data.len() < 2 && data != EMPTY_FRAME {
+        return Err(CompressionError::InputTooSmall(codec));
+    }
+
+    let input_ptr = data.as_ptr() as *const c_char;
+    let input_len = data.len() as c_ulong;
+    let (start, len) = (start as c_ulong, len as c_ulong);
+
+    let decompressed_c_data = unsafe {
+        match codec {
+            Codec::Zlib => decompress_data_range(input_ptr, input_len, start, len),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => decompress_data_range_lz4(input_ptr, input_len, start, len),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => decompress_data_range_zstd(input_ptr, input_len, start, len),
+        }
+    };
+
+    if decompressed_c_data.buffer.is_null() {
+        return Err(decompress_error(&decompressed_c_data, codec));
+    }
+
+    let rust_vec = unsafe {
+        let slice = slice::from_raw_parts(decompressed_c_data.buffer as *const u8, decompressed_c_data.length as usize);
+        let vec = slice.to_vec();
+        free_decompressed_data(decompressed_c_data);
+        vec
+    };
+
+    Ok(rust_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompress_cow_borrows_reused_buffer() {
+        let mut buf = Vec::new();
+        for codec in Codec::ALL {
+            for original_data in ["A message long enough to set the buffer's capacity. ".repeat(8), "short".to_string()] {
+                let compressed = codec.compress(&original_data).unwrap();
+                let capacity_before = buf.capacity();
+
+                let text = decompress_cow(&compressed, &mut buf, codec).expect("Decompression into the buffer should work");
+                assert!(matches!(text, Cow::Borrowed(_)), "{} result should borrow the buffer", codec);
+                assert_eq!(text, original_data);
+                let text_ptr = text.as_ptr();
+
+                assert_eq!(text_ptr, buf.as_ptr(), "The text should live in the caller's buffer");
+                if original_data.len() <= capacity_before {
+                    assert_eq!(buf.capacity(), capacity_before, "A large enough buffer should be reused");
+                }
+            }
+        }
+
+        assert_eq!(decompress_cow(&[0x05, 0xFF, 0xFF], &mut buf, Codec::Zlib), Err(CompressionError::CorruptData(Codec::Zlib)));
+    }
+
+    #[test]
+    fn test_decompress_append_concatenates_frames() {
+        let frames: [&[u8]; 3] = [b"first frame, ", &[0x00, 0xFF, 0x80, 0x01], &b"third frame repeats third frame repeats ".repeat(30)];
+
+        for codec in Codec::ALL {
+            let mut out = b"existing:".to_vec();
+            for frame in frames {
+                let compressed = compress_rust_bytes(frame, codec).unwrap();
+                let appended = decompress_append(&compressed, codec, &mut out).expect("Appending decompression should work");
+                assert_eq!(appended, frame.len(), "{} should report the bytes it appended", codec);
+            }
+            assert_eq!(out, [&b"existing:"[..], &frames.concat()].concat(), "{} output should be the concatenation", codec);
+
+            let before = out.clone();
+            assert!(decompress_append(&[0x05, 0xFF, 0xFF], codec, &mut out).is_err());
+            assert_eq!(out, before, "A failed append should leave the buffer unchanged");
+        }
+    }
+
+    #[test]
+    fn test_empty_frame_accepted_everywhere() {
+        for codec in Codec::ALL {
+            assert_eq!(decompress_rust_data_bytes(&EMPTY_FRAME, codec), Ok(Vec::new()), "{} bytes decompression", codec);
+            assert_eq!(codec.decompress(&EMPTY_FRAME), Ok(String::new()), "{} string decompression", codec);
+            assert_eq!(decompress_range(&EMPTY_FRAME, codec, 0, 10), Ok(Vec::new()), "{} range decompression", codec);
+
+            let mut out = b"kept".to_vec();
+            assert_eq!(decompress_append(&EMPTY_FRAME, codec, &mut out), Ok(0));
+            assert_eq!(out, b"kept");
+
+            // A header promising data still needs a payload
+            assert_eq!(decompress_rust_data_bytes(&[0x05], codec), Err(CompressionError::InputTooSmall(codec)));
+            assert!(decompress_range(&[0x80, 0x01], codec, 0, 10).is_err());
+        }
+        assert_eq!(crate::decompress_rust_data_deflate_raw(&EMPTY_FRAME), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_bytes_round_trip_binary() {
+        // Null bytes and invalid UTF-8 are both rejected by the &str wrappers
+        let data: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).chain([0, 0xFF, 0xFE]).collect();
+
+        for codec in Codec::ALL {
+            let compressed = compress_rust_bytes(&data, codec).expect("Bytes compression should work");
+            let decompressed = decompress_rust_data_bytes(&compressed, codec).expect("Bytes decompression should work");
+            assert_eq!(decompressed, data, "{} bytes round trip should preserve the data", codec);
+            assert_eq!(codec.decompress(&compressed), Err(CompressionError::InvalidUtf8(codec)));
+        }
+    }
+
+    #[test]
+    fn test_bytes_matches_str_output() {
+        let text = "Same bytes in, same bytes out. ".repeat(8);
+        for codec in Codec::ALL {
+            assert_eq!(compress_rust_bytes(text.as_bytes(), codec), codec.compress(&text), "{} output should match", codec);
+        }
+    }
+
+    #[test]
+    fn test_decompress_range_matches_slice() {
+        let original: Vec<u8> = (0..50_000u32).flat_map(|i| format!("record {:05} ", i).into_bytes()).collect();
+
+        for codec in Codec::ALL {
+            let compressed = compress_rust_bytes(&original, codec).expect("Bytes compression should work");
+            for (start, len) in [(1000, 100), (0, 17), (original.len() - 5, 5), (300_000, 70_000)] {
+                let range = decompress_range(&compressed, codec, start, len).expect("Range decompression should work");
+                assert_eq!(range, original[start..start + len], "{} range {}..{} should match the original", codec, start, start + len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompress_range_clamped_and_truncated() {
+        let original = b"0123456789".repeat(100);
+
+        for codec in Codec::ALL {
+            let compressed = compress_rust_bytes(&original, codec).expect("Bytes compression should work");
+            assert_eq!(decompress_range(&compressed, codec, 990, 100), Ok(original[990..].to_vec()), "{} should clamp to the end", codec);
+            assert_eq!(decompress_range(&compressed, codec, 5000, 10), Ok(Vec::new()), "{} range past the end is empty", codec);
+
+            let truncated = &compressed[..compressed.len() / 2];
+            assert_eq!(decompress_range(truncated, codec, 900, 100), Err(CompressionError::CorruptData(codec)),
+                "{} should fail when the payload ends before the range", codec);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! A single summary of what this build can do, for diagnostics and `--version`-style output.
+
+use std::ops::RangeInclusive;
+
+use crate::codec::{available_codecs, Codec};
+use crate::version::FORMAT_VERSION;
+
+/// What one compiled-in codec supports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodecCapabilities {
+    pub codec: Codec,
+    /// Version of the C library linked at runtime, see `Codec::library_version`.
+    pub library_version: &'static str,
+    /// Largest input in bytes, see `Codec::max_input_size`.
+    pub max_input_size: usize,
+    /// Accepted compression levels, or `None` if the codec has no level setting.
+    pub level_range: Option<RangeInclusive<i32>>,
+}
+
+/// Everything `capabilities` reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Codecs compiled into this build, in `available_codecs` order.
+    pub codecs: Vec<CodecCapabilities>,
+    /// Content checksums (`compress_with_content_crc`). Always available.
+    pub checksums: bool,
+    /// Preset dictionaries. Not supported by this crate.
+    pub dictionaries: bool,
+    /// Incremental zstd compression (`ZstdStreamCompressor`, `CompressWriter`), which
+    /// needs the `zstd` feature.
+    pub streaming: bool,
+    /// Async read/write adapters. Not supported by this crate.
+    pub async_io: bool,
+    /// Container format version written by `compress_framed` and `compress_tagged`.
+    pub format_version: u8,
+}
+
+/// Reports the codecs, optional features, input limits and linked library versions of
+/// this build in one call.
+///
+/// Meant for diagnostic tools and verbose version output; the values never change
+/// while the process runs.
+pub fn capabilities() -> Capabilities {
+    let codecs = available_codecs()
+        .iter()
+        .map(|&codec| CodecCapabilities {
+            codec,
+            library_version: codec.library_version(),
+            max_input_size: codec.max_input_size(),
+            level_range: codec.level_range(),
+        })
+        .collect();
+
+    Capabilities {
+        codecs,
+        checksums: true,
+        dictionaries: false,
+        streaming: cfg!(feature = "zstd"),
+        async_io: false,
+        format_version: FORMAT_VERSION,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_match_build() {
+        let caps = capabilities();
+
+        let codecs: Vec<Codec> = caps.codecs.iter().map(|info| info.codec).collect();
+        assert_eq!(codecs, available_codecs());
+        assert_eq!(available_codecs(), Codec::ALL);
+        for info in &caps.codecs {
+            assert!(!info.library_version.is_empty(), "{} library version should not be empty", info.codec);
+            assert!(
+                info.library_version.starts_with(|c: char| c.is_ascii_digit()),
+                "{} library version {:?} should start with a number",
+                info.codec,
+                info.library_version
+            );
+            assert!(info.max_input_size > 0);
+            assert_eq!(info.level_range, info.codec.level_range());
+        }
+
+        assert_eq!(caps.streaming, cfg!(feature = "zstd"));
+        assert_eq!(caps.format_version, FORMAT_VERSION);
+    }
+}
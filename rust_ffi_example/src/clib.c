@@ -1,9 +1,20 @@
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <stdint.h>
+#include <limits.h>
 #include <zlib.h>
+// LZ4 and zstd are optional; build.rs defines HAVE_LZ4 / HAVE_ZSTD for the enabled
+// cargo features so a zlib-only build neither needs nor links them.
+#ifdef HAVE_LZ4
 #include <lz4.h>
+#endif
+#ifdef HAVE_ZSTD
+// ZSTD_c_targetCBlockSize is still in zstd's experimental section before v1.5.6
+#define ZSTD_STATIC_LINKING_ONLY
 #include <zstd.h>
+#include <zstd_errors.h>
+#endif
 
 // Define a struct to return both buffer and length
 typedef struct {
@@ -17,6 +28,30 @@ typedef struct {
     unsigned long length;
 } DecompressedData;
 
+// Error codes reported in DecompressedData.length when buffer is NULL.
+// Callers that only check for a NULL buffer keep working unchanged.
+#define DECOMPRESS_ERR_GENERIC 0
+// The varint header disagrees with the size the payload actually decodes to
+#define DECOMPRESS_ERR_HEADER_MISMATCH 1
+// A zstd frame needs a larger window than the caller allowed
+#define DECOMPRESS_ERR_WINDOW_TOO_LARGE 2
+// The input is not valid data for the codec: a malformed header, a corrupt or truncated
+// payload, or data written by a different codec. A header claiming more than the 100MB
+// the decompressors will allocate is reported this way too, as it comes from the input.
+#define DECOMPRESS_ERR_CORRUPT 3
+
+// A varint header of 0 with no payload after it is the canonical empty frame, and
+// every decompressor returns an empty result for it. The buffer is still allocated
+// (and must be freed as usual) so callers can tell success from a NULL failure.
+static DecompressedData empty_decompressed_data(void) {
+    DecompressedData result = {NULL, 0};
+    result.buffer = (char *)calloc(1, 1);
+    if (result.buffer == NULL) {
+        perror("Failed to allocate memory for empty frame");
+    }
+    return result;
+}
+
 // Variable-byte encoding functions
 
 // Encode a length as variable-byte encoding
@@ -63,10 +98,99 @@ int decode_varint(const char *buffer, int max_bytes, unsigned long *value) {
     return -1; // Incomplete varint
 }
 
+// MSB-first ("big-endian") variable-byte encoding, as used for Git's OFS_DELTA offsets.
+// The most significant 7-bit group comes first and every byte except the last has the
+// continuation bit set. Each continuation also adds one to the remaining value, so
+// every value has exactly one encoding. NOT interchangeable with encode_varint/decode_varint.
+
+// Encode a value MSB-first. buffer must hold at least 10 bytes.
+// Returns the number of bytes written
+int encode_varint_msb(unsigned long value, char *buffer) {
+    unsigned char tmp[10];
+    int pos = sizeof(tmp) - 1;
+    tmp[pos] = (unsigned char)(value & 0x7F);
+    while (value >>= 7) {
+        value--;
+        tmp[--pos] = (unsigned char)(0x80 | (value & 0x7F));
+    }
+    int bytes_written = (int)sizeof(tmp) - pos;
+    memcpy(buffer, tmp + pos, bytes_written);
+    return bytes_written;
+}
+
+// Decode an MSB-first variable-byte value
+// Returns the number of bytes read, or -1 on error
+int decode_varint_msb(const char *buffer, int max_bytes, unsigned long *value) {
+    if (max_bytes <= 0) {
+        return -1;
+    }
+    int bytes_read = 0;
+    unsigned char byte = (unsigned char)buffer[bytes_read++];
+    unsigned long result = byte & 0x7F;
+
+    while (byte & 0x80) {
+        if (bytes_read >= max_bytes) {
+            #ifdef DEBUG_FUZZING
+            fprintf(stderr, "Incomplete MSB varint: unexpected end of data\n");
+            #endif
+            return -1;
+        }
+        // result + 1 must still fit after shifting in another 7 bits
+        if (result >= (~0UL >> 7)) {
+            #ifdef DEBUG_FUZZING
+            fprintf(stderr, "MSB varint overflow: value too large\n");
+            #endif
+            return -1;
+        }
+        byte = (unsigned char)buffer[bytes_read++];
+        result = ((result + 1) << 7) | (byte & 0x7F);
+    }
+
+    *value = result;
+    return bytes_read;
+}
+
+// Decode a variable-byte encoded length a word at a time.
+// Same contract and results as decode_varint. The word-at-a-time path reads 8 bytes with
+// one aligned load, so it is only taken when `buffer` is 8-byte aligned and at least
+// 8 bytes are readable; any other input (including pointers into the middle of a
+// Rust slice) falls back to the scalar decoder. Callers never need to align input.
+int decode_varint_swar(const char *buffer, int max_bytes, unsigned long *value) {
+#if defined(__BYTE_ORDER__) && __BYTE_ORDER__ == __ORDER_LITTLE_ENDIAN__
+    if (max_bytes >= 8 && ((uintptr_t)buffer & (sizeof(uint64_t) - 1)) == 0) {
+        uint64_t word;
+        memcpy(&word, __builtin_assume_aligned(buffer, sizeof(uint64_t)), sizeof(word));
+
+        // One bit set per byte whose continuation bit is clear; the lowest marks the last byte
+        uint64_t stops = ~word & 0x8080808080808080ULL;
+        if (stops != 0) {
+            int len = (__builtin_ctzll(stops) >> 3) + 1;
+            uint64_t result = 0;
+            for (int i = 0; i < len; i++) {
+                result |= ((word >> (8 * i)) & 0x7F) << (7 * i);
+            }
+            *value = (unsigned long)result;
+            return len;
+        }
+        // Longer than 8 bytes: let the scalar decoder handle the tail and overflow checks
+    }
+#endif
+    return decode_varint(buffer, max_bytes, value);
+}
+
 // Function to compress a string using zlib with variable-byte length header
 // The compressed data format: [varint original length][zlib compressed data]
 // The caller is responsible for freeing the returned buffer
-CompressedData compress_string(const char *input, unsigned long input_len) {
+// level is a zlib level from 0 (store) to 9 (best), or Z_DEFAULT_COMPRESSION
+CompressedData compress_string_level(const char *input, unsigned long input_len, int level) {
+    // Reject out-of-range levels before allocating, matching compress_string_zstd_level
+    if (level != Z_DEFAULT_COMPRESSION && (level < 0 || level > 9)) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "zlib compression level %d out of range [0, 9]\n", level);
+        #endif
+        return (CompressedData){NULL, 0};
+    }
+
     unsigned long compressed_bound = compressBound(input_len);
     // Allocate buffer for: max 10-byte varint header + compressed data
     unsigned long total_buffer_size = 10 + compressed_bound;
@@ -83,7 +207,7 @@ CompressedData compress_string(const char *input, unsigned long input_len) {
 
     // Compress data after the varint header
     unsigned long compressed_len = compressed_bound;
-    int res = compress((Bytef *)(output_buffer + header_size), &compressed_len, (const Bytef *)input, input_len);
+    int res = compress2((Bytef *)(output_buffer + header_size), &compressed_len, (const Bytef *)input, input_len, level);
 
     if (res != Z_OK) {
         fprintf(stderr, "Compression failed: %d\n", res);
@@ -96,17 +220,53 @@ CompressedData compress_string(const char *input, unsigned long input_len) {
     return result;
 }
 
+// Worst-case size of compress_string output for input_len bytes:
+// max 10-byte varint header + zlib's compressBound
+unsigned long compress_string_bound(unsigned long input_len) {
+    return 10 + compressBound(input_len);
+}
+
+// Function to compress a string using zlib into a caller-provided buffer
+// The compressed data format: [varint original length][zlib compressed data]
+// Returns the number of bytes written, or 0 if output_cap is too small or compression fails.
+// Sizing output_cap with compress_string_bound always leaves enough room.
+unsigned long compress_string_into(const char *input, unsigned long input_len, char *output, unsigned long output_cap) {
+    if (output_cap < compress_string_bound(input_len)) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "Output buffer of %lu bytes is below the compress bound\n", output_cap);
+        #endif
+        return 0;
+    }
+
+    int header_size = encode_varint(input_len, output);
+    unsigned long compressed_len = output_cap - header_size;
+    int res = compress2((Bytef *)(output + header_size), &compressed_len, (const Bytef *)input, input_len, Z_DEFAULT_COMPRESSION);
+
+    if (res != Z_OK) {
+        fprintf(stderr, "Compression failed: %d\n", res);
+        return 0;
+    }
+    return header_size + compressed_len;
+}
+
+// Function to compress a string using zlib at the default level
+// The compressed data format: [varint original length][zlib compressed data]
+// The caller is responsible for freeing the returned buffer
+CompressedData compress_string(const char *input, unsigned long input_len) {
+    return compress_string_level(input, input_len, Z_DEFAULT_COMPRESSION);
+}
+
 // Function to decompress data using zlib, automatically reading original size from varint header
 // Expects input format: [varint original length][zlib compressed data]
 // The caller is responsible for freeing the returned buffer
 DecompressedData decompress_data(const char *input, unsigned long input_len) {
     DecompressedData result = {NULL, 0};
     
-    // Check minimum input size (at least 1 byte for varint + some compressed data)
-    if (input_len < 2) {
+    // Check minimum input size (at least 1 byte for the varint header)
+    if (input_len < 1) {
         // Reduce noise during fuzzing - only print in debug mode
         #ifdef DEBUG_FUZZING
-        fprintf(stderr, "Invalid compressed data: too small (need at least 2 bytes)\n");
+        fprintf(stderr, "Invalid compressed data: empty input\n");
         #endif
         return result;
     }
@@ -119,15 +279,21 @@ DecompressedData decompress_data(const char *input, unsigned long input_len) {
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid compressed data: failed to decode varint header\n");
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
     
+    if ((unsigned long)header_size == input_len && original_len == 0) {
+        return empty_decompressed_data();
+    }
+
     // Check that we have enough data after the header
     if ((unsigned long)header_size >= input_len) {
         // Reduce noise during fuzzing - only print in debug mode
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid compressed data: no data after varint header\n");
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
     
@@ -136,6 +302,7 @@ DecompressedData decompress_data(const char *input, unsigned long input_len) {
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid compressed data: original length too large (%lu bytes)\n", original_len);
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
     
@@ -147,16 +314,35 @@ DecompressedData decompress_data(const char *input, unsigned long input_len) {
     }
 
     // Decompress data (skip the varint header)
-    unsigned long actual_output_len = original_len;
+    // Offer the spare byte from the allocation so that a payload larger than the header
+    // claims is reported as a size mismatch. zlib treats a zero-sized destination
+    // specially and would report it as Z_DATA_ERROR instead.
+    unsigned long actual_output_len = original_len + 1;
     int res = uncompress((Bytef *)output_buffer, &actual_output_len, 
                         (const Bytef *)(input + header_size), input_len - header_size);
 
+    // Z_BUF_ERROR means the payload inflates to more than the header declared
+    // (including a header of 0 followed by a non-empty stream)
+    if (res == Z_BUF_ERROR) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "Decompression length mismatch: payload larger than %lu bytes\n", original_len);
+        #endif
+        free(output_buffer);
+        result.length = DECOMPRESS_ERR_HEADER_MISMATCH;
+        return result;
+    }
+
     if (res != Z_OK) {
         // Reduce noise during fuzzing - only print in debug mode
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Decompression failed: %d\n", res);
         #endif
         free(output_buffer);
+        // uncompress reports a bad or truncated stream as Z_DATA_ERROR; anything else
+        // (Z_MEM_ERROR) is a failure on our side
+        if (res == Z_DATA_ERROR) {
+            result.length = DECOMPRESS_ERR_CORRUPT;
+        }
         return result;
     }
     
@@ -167,6 +353,164 @@ DecompressedData decompress_data(const char *input, unsigned long input_len) {
                 original_len, actual_output_len);
         #endif
         free(output_buffer);
+        result.length = DECOMPRESS_ERR_HEADER_MISMATCH;
+        return result;
+    }
+
+    result.buffer = output_buffer;
+    result.length = actual_output_len;
+    return result;
+}
+
+// CRC-32 (IEEE, as used by gzip and PNG) of a buffer, computed with zlib.
+// crc32 takes a uInt length, so buffers of 4 GiB or more are fed in UINT_MAX-sized chunks.
+unsigned long content_crc32(const char *input, unsigned long input_len) {
+    uLong crc = crc32(0L, Z_NULL, 0);
+    while (input_len > 0) {
+        uInt chunk = input_len > UINT_MAX ? UINT_MAX : (uInt)input_len;
+        crc = crc32(crc, (const Bytef *)input, chunk);
+        input += chunk;
+        input_len -= chunk;
+    }
+    return crc;
+}
+
+// Version of the zlib library linked at runtime, e.g. "1.2.13"
+const char *zlib_version_string(void) {
+    return zlibVersion();
+}
+
+// Function to compress a string as raw deflate (no zlib wrapper) with variable-byte length header
+// The compressed data format: [varint original length][raw deflate data]
+// Negative windowBits drop the 2-byte zlib header and the 4-byte Adler-32 trailer;
+// the deflate stream itself is identical to what compress_string produces.
+// The caller is responsible for freeing the returned buffer
+CompressedData compress_string_deflate_raw(const char *input, unsigned long input_len) {
+    CompressedData result = {NULL, 0};
+    z_stream strm;
+    memset(&strm, 0, sizeof(strm));
+
+    // Same level and memLevel as compress(), so only the wrapper differs
+    if (deflateInit2(&strm, Z_DEFAULT_COMPRESSION, Z_DEFLATED, -MAX_WBITS, 8, Z_DEFAULT_STRATEGY) != Z_OK) {
+        fprintf(stderr, "Raw deflate initialization failed\n");
+        return result;
+    }
+
+    unsigned long compressed_bound = deflateBound(&strm, input_len);
+    char *output_buffer = (char *)malloc(10 + compressed_bound);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for compression");
+        deflateEnd(&strm);
+        return result;
+    }
+
+    int header_size = encode_varint(input_len, output_buffer);
+
+    // avail_in and avail_out are uInt, so buffers of 4 GiB or more are handed to deflate
+    // in UINT_MAX-sized slices, as compress2 does
+    unsigned long left_in = input_len;
+    unsigned long left_out = compressed_bound;
+    strm.next_in = (Bytef *)input;
+    strm.next_out = (Bytef *)(output_buffer + header_size);
+
+    int res;
+    do {
+        if (strm.avail_in == 0 && left_in > 0) {
+            strm.avail_in = left_in > UINT_MAX ? UINT_MAX : (uInt)left_in;
+            left_in -= strm.avail_in;
+        }
+        if (strm.avail_out == 0) {
+            strm.avail_out = left_out > UINT_MAX ? UINT_MAX : (uInt)left_out;
+            left_out -= strm.avail_out;
+        }
+        res = deflate(&strm, left_in > 0 ? Z_NO_FLUSH : Z_FINISH);
+    } while (res == Z_OK);
+    unsigned long compressed_len = strm.total_out;
+    deflateEnd(&strm);
+
+    if (res != Z_STREAM_END) {
+        fprintf(stderr, "Raw deflate compression failed: %d\n", res);
+        free(output_buffer);
+        return result;
+    }
+
+    result.buffer = output_buffer;
+    result.length = header_size + compressed_len;
+    return result;
+}
+
+// Function to decompress raw deflate data, automatically reading original size from varint header
+// Expects input format: [varint original length][raw deflate data]
+// There is no checksum, so the header length check is the only integrity check
+// The caller is responsible for freeing the returned buffer
+DecompressedData decompress_data_deflate_raw(const char *input, unsigned long input_len) {
+    DecompressedData result = {NULL, 0};
+
+    if (input_len < 1) {
+        return result;
+    }
+
+    unsigned long original_len;
+    int header_size = decode_varint(input, input_len, &original_len);
+    if (header_size < 0) {
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+    if ((unsigned long)header_size == input_len && original_len == 0) {
+        return empty_decompressed_data();
+    }
+    if ((unsigned long)header_size >= input_len) {
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    // Sanity check on original length (prevent absurdly large allocations)
+    if (original_len > 100 * 1024 * 1024) { // 100MB limit
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    char *output_buffer = (char *)calloc(original_len + 1, 1);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for decompression");
+        return result;
+    }
+
+    z_stream strm;
+    memset(&strm, 0, sizeof(strm));
+    if (inflateInit2(&strm, -MAX_WBITS) != Z_OK) {
+        free(output_buffer);
+        return result;
+    }
+
+    strm.next_in = (Bytef *)(input + header_size);
+    strm.avail_in = (uInt)(input_len - header_size);
+    strm.next_out = (Bytef *)output_buffer;
+    // Spare byte, as in decompress_data, so oversized payloads show up as a mismatch
+    strm.avail_out = (uInt)(original_len + 1);
+
+    int res = inflate(&strm, Z_FINISH);
+    unsigned long actual_output_len = strm.total_out;
+    inflateEnd(&strm);
+
+    if (res != Z_STREAM_END) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "Raw inflate failed: %d\n", res);
+        #endif
+        free(output_buffer);
+        // Out of output space before the end of the stream means more data than declared
+        if (res == Z_BUF_ERROR && strm.avail_out == 0) {
+            result.length = DECOMPRESS_ERR_HEADER_MISMATCH;
+        } else if (res == Z_DATA_ERROR || res == Z_BUF_ERROR) {
+            // A bad stream, or one that ends before its final block
+            result.length = DECOMPRESS_ERR_CORRUPT;
+        }
+        return result;
+    }
+
+    if (actual_output_len != original_len) {
+        free(output_buffer);
+        result.length = DECOMPRESS_ERR_HEADER_MISMATCH;
         return result;
     }
 
@@ -175,6 +519,79 @@ DecompressedData decompress_data(const char *input, unsigned long input_len) {
     return result;
 }
 
+// Incremental zlib decompression, for input that arrives in chunks. The stream is an
+// opaque z_stream fed with zlib_dstream_decompress and released with zlib_dstream_free.
+// It reads a bare zlib stream: callers strip the varint length header themselves.
+
+// Creates a decompression stream, or returns NULL if allocation fails
+void *zlib_dstream_create(void) {
+    z_stream *strm = (z_stream *)calloc(1, sizeof(z_stream));
+    if (strm == NULL) {
+        return NULL;
+    }
+    if (inflateInit(strm) != Z_OK) {
+        free(strm);
+        return NULL;
+    }
+    return strm;
+}
+
+// Decompresses as much of input as fits in max_output bytes of output.
+// *consumed is set to the input bytes used and *finished to 1 once the end of the zlib
+// stream is reached. If the output came back full, call again (with the unconsumed
+// input, possibly none) to collect the rest before reading more input.
+// On success the buffer is non-NULL even when no output was produced (length 0).
+DecompressedData zlib_dstream_decompress(void *stream, const char *input, unsigned long input_len,
+                                         unsigned long max_output, unsigned long *consumed, int *finished) {
+    DecompressedData result = {NULL, 0};
+    z_stream *strm = (z_stream *)stream;
+    *consumed = 0;
+    *finished = 0;
+
+    // +1 so an empty result still gets a non-NULL buffer
+    char *output_buffer = (char *)malloc(max_output + 1);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for zlib stream decompression");
+        return result;
+    }
+
+    strm->next_in = (Bytef *)input;
+    strm->avail_in = (uInt)input_len;
+    strm->next_out = (Bytef *)output_buffer;
+    strm->avail_out = (uInt)max_output;
+
+    int res = inflate(strm, Z_NO_FLUSH);
+    // Z_BUF_ERROR only means no progress was possible with this input and output space
+    if (res != Z_OK && res != Z_STREAM_END && res != Z_BUF_ERROR) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "zlib stream decompression failed: %d\n", res);
+        #endif
+        free(output_buffer);
+        // Z_NEED_DICT means a preset dictionary this library never writes
+        if (res == Z_DATA_ERROR || res == Z_NEED_DICT) {
+            result.length = DECOMPRESS_ERR_CORRUPT;
+        }
+        return result;
+    }
+
+    *consumed = input_len - strm->avail_in;
+    *finished = res == Z_STREAM_END;
+    result.buffer = output_buffer;
+    result.length = max_output - strm->avail_out;
+    return result;
+}
+
+void zlib_dstream_free(void *stream) {
+    inflateEnd((z_stream *)stream);
+    free(stream);
+}
+
+#ifdef HAVE_LZ4
+// Version of the LZ4 library linked at runtime, e.g. "1.9.4"
+const char *lz4_version_string(void) {
+    return LZ4_versionString();
+}
+
 // Function to compress a string using LZ4 with variable-byte length header
 // The compressed data format: [varint original length][LZ4 compressed data]
 // The caller is responsible for freeing the returned buffer
@@ -223,10 +640,10 @@ CompressedData compress_string_lz4(const char *input, unsigned long input_len) {
 DecompressedData decompress_data_lz4(const char *input, unsigned long input_len) {
     DecompressedData result = {NULL, 0};
 
-    // Check minimum input size (at least 1 byte for varint + some compressed data)
-    if (input_len < 2) {
+    // Check minimum input size (at least 1 byte for the varint header)
+    if (input_len < 1) {
         #ifdef DEBUG_FUZZING
-        fprintf(stderr, "Invalid LZ4 compressed data: too small (need at least 2 bytes)\n");
+        fprintf(stderr, "Invalid LZ4 compressed data: empty input\n");
         #endif
         return result;
     }
@@ -238,14 +655,20 @@ DecompressedData decompress_data_lz4(const char *input, unsigned long input_len)
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid LZ4 compressed data: failed to decode varint header\n");
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
 
+    if ((unsigned long)header_size == input_len && original_len == 0) {
+        return empty_decompressed_data();
+    }
+
     // Check that we have enough data after the header
     if ((unsigned long)header_size >= input_len) {
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid LZ4 compressed data: no data after varint header\n");
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
 
@@ -255,22 +678,10 @@ DecompressedData decompress_data_lz4(const char *input, unsigned long input_len)
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid LZ4 compressed data: original length too large (%lu bytes)\n", original_len);
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
     
-    if (original_len == 0) { // Handle zero-length original string case
-        char *output_buffer = (char *)calloc(1, 1); // calloc zero-initializes memory
-        if (output_buffer == NULL) {
-            perror("Failed to allocate memory for LZ4 decompression (empty string)");
-            return result;
-        }
-        output_buffer[0] = '\0';
-        result.buffer = output_buffer;
-        result.length = 0;
-        return result;
-    }
-
-
     // Allocate buffer for decompressed data
     char *output_buffer = (char *)calloc(original_len + 1, 1); // calloc zero-initializes memory
     if (output_buffer == NULL) {
@@ -279,13 +690,21 @@ DecompressedData decompress_data_lz4(const char *input, unsigned long input_len)
     }
 
     // Decompress data (skip the varint header)
-    int decompressed_size = LZ4_decompress_safe(input + header_size, output_buffer, (int)(input_len - header_size), (int)original_len);
+    // Decode up to one byte more than the header claims, into the spare byte of the
+    // allocation. LZ4_decompress_safe with exactly original_len of room fails the same
+    // way for a block that expands further as for a corrupt one; this way a block that
+    // under-reports its size decodes original_len + 1 bytes and is caught as a mismatch
+    // below, as is a zero-length header followed by a non-empty block.
+    int decompressed_size = LZ4_decompress_safe_partial(input + header_size, output_buffer, (int)(input_len - header_size),
+                                                        (int)original_len + 1, (int)original_len + 1);
 
     if (decompressed_size < 0) {
         #ifdef DEBUG_FUZZING
-        fprintf(stderr, "LZ4_decompress_safe failed: %d\n", decompressed_size);
+        fprintf(stderr, "LZ4_decompress_safe_partial failed: %d\n", decompressed_size);
         #endif
         free(output_buffer);
+        // LZ4 allocates nothing, so a negative result always means a malformed block
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
 
@@ -296,6 +715,7 @@ DecompressedData decompress_data_lz4(const char *input, unsigned long input_len)
                 original_len, decompressed_size);
         #endif
         free(output_buffer);
+        result.length = DECOMPRESS_ERR_HEADER_MISMATCH;
         return result;
     }
     
@@ -305,11 +725,22 @@ DecompressedData decompress_data_lz4(const char *input, unsigned long input_len)
     result.length = (unsigned long)decompressed_size;
     return result;
 }
+#endif // HAVE_LZ4
 
+#ifdef HAVE_ZSTD
 // Function to compress a string using Zstandard (zstd) with variable-byte length header
+// at an explicit compression level. Negative levels select zstd's fast modes.
 // The compressed data format: [varint original length][ZSTD compressed data]
 // The caller is responsible for freeing the returned buffer
-CompressedData compress_string_zstd(const char *input, unsigned long input_len) {
+CompressedData compress_string_zstd_level(const char *input, unsigned long input_len, int level) {
+    // zstd silently clamps out-of-range levels; reject them instead so callers get what they asked for
+    if (level < ZSTD_minCLevel() || level > ZSTD_maxCLevel()) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "ZSTD compression level %d out of range [%d, %d]\n", level, ZSTD_minCLevel(), ZSTD_maxCLevel());
+        #endif
+        return (CompressedData){NULL, 0};
+    }
+
     // Calculate the maximum compressed size using ZSTD_compressBound
     size_t zstd_max_compressed_size = ZSTD_compressBound(input_len);
     if (ZSTD_isError(zstd_max_compressed_size)) {
@@ -338,7 +769,7 @@ CompressedData compress_string_zstd(const char *input, unsigned long input_len)
         total_buffer_size - header_size,
         input, 
         input_len,
-        1 // Default compression level
+        level
     );
 
     if (ZSTD_isError(compressed_data_size)) {
@@ -354,16 +785,57 @@ CompressedData compress_string_zstd(const char *input, unsigned long input_len)
     return result;
 }
 
+// Function to compress a string using Zstandard (zstd) with variable-byte length header
+// The compressed data format: [varint original length][ZSTD compressed data]
+// The caller is responsible for freeing the returned buffer
+CompressedData compress_string_zstd(const char *input, unsigned long input_len) {
+    return compress_string_zstd_level(input, input_len, 1); // Default compression level
+}
+
+// Lowest (fastest) compression level accepted by compress_string_zstd_level; negative
+int zstd_min_compression_level(void) {
+    return ZSTD_minCLevel();
+}
+
+// Highest (strongest) compression level accepted by compress_string_zstd_level
+int zstd_max_compression_level(void) {
+    return ZSTD_maxCLevel();
+}
+
+// Version of the zstd library linked at runtime, e.g. "1.5.5"
+const char *zstd_version_string(void) {
+    return ZSTD_versionString();
+}
+
+// Memory ZSTD_decompress needs for its decompression context, beyond the input and
+// output buffers. One-shot decompression into a flat buffer needs no window buffer.
+unsigned long zstd_decompress_working_set(void) {
+    return ZSTD_estimateDCtxSize();
+}
+
+// Maps a zstd decompression error to the DECOMPRESS_ERR_* code reported to Rust.
+// Only an allocation failure is our fault; every other error code describes the input.
+static unsigned long zstd_decompress_error_code(size_t error) {
+    switch (ZSTD_getErrorCode(error)) {
+    case ZSTD_error_dstSize_tooSmall:
+        return DECOMPRESS_ERR_HEADER_MISMATCH;
+    case ZSTD_error_memory_allocation:
+        return DECOMPRESS_ERR_GENERIC;
+    default:
+        return DECOMPRESS_ERR_CORRUPT;
+    }
+}
+
 // Function to decompress data using Zstandard (zstd), automatically reading original size from varint header
 // Expects input format: [varint original length][ZSTD compressed data]
 // The caller is responsible for freeing the returned buffer
 DecompressedData decompress_data_zstd(const char *input, unsigned long input_len) {
     DecompressedData result = {NULL, 0};
 
-    // Check minimum input size (at least 1 byte for varint + some compressed data)
-    if (input_len < 2) {
+    // Check minimum input size (at least 1 byte for the varint header)
+    if (input_len < 1) {
         #ifdef DEBUG_FUZZING
-        fprintf(stderr, "Invalid ZSTD compressed data: too small (need at least 2 bytes)\n");
+        fprintf(stderr, "Invalid ZSTD compressed data: empty input\n");
         #endif
         return result;
     }
@@ -375,14 +847,20 @@ DecompressedData decompress_data_zstd(const char *input, unsigned long input_len
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid ZSTD compressed data: failed to decode varint header\n");
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
 
+    if ((unsigned long)header_size == input_len && original_len == 0) {
+        return empty_decompressed_data();
+    }
+
     // Check that we have enough data after the header
     if ((unsigned long)header_size >= input_len) {
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid ZSTD compressed data: no data after varint header\n");
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
 
@@ -392,10 +870,23 @@ DecompressedData decompress_data_zstd(const char *input, unsigned long input_len
         #ifdef DEBUG_FUZZING
         fprintf(stderr, "Invalid ZSTD compressed data: original length too large (%lu bytes)\n", original_len);
         #endif
+        result.length = DECOMPRESS_ERR_CORRUPT;
         return result;
     }
     
     if (original_len == 0) { // Handle zero-length original string case
+        // The payload must also decode to nothing; with no room for output,
+        // a frame carrying data fails with dstSize_tooSmall.
+        char probe;
+        size_t probed = ZSTD_decompress(&probe, 0, input + header_size, input_len - header_size);
+        if (ZSTD_isError(probed)) {
+            #ifdef DEBUG_FUZZING
+            fprintf(stderr, "ZSTD_decompress failed: %s\n", ZSTD_getErrorName(probed));
+            #endif
+            result.length = zstd_decompress_error_code(probed);
+            return result;
+        }
+
         char *output_buffer = (char *)calloc(1, 1); 
         if (output_buffer == NULL) {
             perror("Failed to allocate memory for ZSTD decompression (empty string)");
@@ -427,6 +918,7 @@ DecompressedData decompress_data_zstd(const char *input, unsigned long input_len
         fprintf(stderr, "ZSTD_decompress failed: %s\n", ZSTD_getErrorName(decompressed_size));
         #endif
         free(output_buffer);
+        result.length = zstd_decompress_error_code(decompressed_size);
         return result;
     }
 
@@ -437,6 +929,7 @@ DecompressedData decompress_data_zstd(const char *input, unsigned long input_len
                 original_len, decompressed_size);
         #endif
         free(output_buffer);
+        result.length = DECOMPRESS_ERR_HEADER_MISMATCH;
         return result;
     }
     
@@ -447,6 +940,558 @@ DecompressedData decompress_data_zstd(const char *input, unsigned long input_len
     return result;
 }
 
+// Smallest and largest values decompress_data_zstd_windowed accepts for max_window_log
+int zstd_min_window_log(void) {
+    return ZSTD_dParam_getBounds(ZSTD_d_windowLogMax).lowerBound;
+}
+
+int zstd_max_window_log(void) {
+    return ZSTD_dParam_getBounds(ZSTD_d_windowLogMax).upperBound;
+}
+
+// Returns 0 if any frame in src declares a window larger than max_window bytes.
+// ZSTD_decompressDCtx only enforces ZSTD_d_windowLogMax when it has to buffer a window,
+// and decoding into a flat buffer never does, so every frame header is checked up front.
+// Malformed frames are left for the decoder to reject.
+static int zstd_frames_fit_window(const char *src, size_t len, unsigned long long max_window) {
+    while (len > 0) {
+        ZSTD_frameHeader header;
+        if (ZSTD_getFrameHeader(&header, src, len) != 0) {
+            return 1;
+        }
+        if (header.frameType == ZSTD_frame && header.windowSize > max_window) {
+            return 0;
+        }
+        size_t frame_size = ZSTD_findFrameCompressedSize(src, len);
+        if (ZSTD_isError(frame_size)) {
+            return 1;
+        }
+        src += frame_size;
+        len -= frame_size;
+    }
+    return 1;
+}
+
+// Like decompress_data_zstd, but refuses frames whose window is larger than
+// 2^max_window_log bytes, so a crafted frame cannot demand a huge window.
+// Such frames fail with DECOMPRESS_ERR_WINDOW_TOO_LARGE.
+// The caller is responsible for freeing the returned buffer
+DecompressedData decompress_data_zstd_windowed(const char *input, unsigned long input_len, int max_window_log) {
+    DecompressedData result = {NULL, 0};
+
+    if (input_len < 1) {
+        return result;
+    }
+
+    unsigned long original_len;
+    int header_size = decode_varint(input, input_len, &original_len);
+    if (header_size < 0) {
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+    if ((unsigned long)header_size == input_len && original_len == 0) {
+        return empty_decompressed_data();
+    }
+    if ((unsigned long)header_size >= input_len) {
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    // Sanity check on original length (prevent absurdly large allocations)
+    if (original_len > 100 * 1024 * 1024) { // 100MB limit
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    const char *payload = input + header_size;
+    size_t payload_len = input_len - header_size;
+    if (!zstd_frames_fit_window(payload, payload_len, 1ULL << max_window_log)) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "ZSTD frame window exceeds 2^%d bytes\n", max_window_log);
+        #endif
+        result.length = DECOMPRESS_ERR_WINDOW_TOO_LARGE;
+        return result;
+    }
+
+    ZSTD_DCtx *dctx = ZSTD_createDCtx();
+    if (dctx == NULL) {
+        return result;
+    }
+    if (ZSTD_isError(ZSTD_DCtx_setParameter(dctx, ZSTD_d_windowLogMax, max_window_log))) {
+        ZSTD_freeDCtx(dctx);
+        return result;
+    }
+
+    char *output_buffer = (char *)calloc(original_len + 1, 1);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for windowed ZSTD decompression");
+        ZSTD_freeDCtx(dctx);
+        return result;
+    }
+
+    size_t decompressed_size = ZSTD_decompressDCtx(dctx, output_buffer, original_len, payload, payload_len);
+    ZSTD_freeDCtx(dctx);
+
+    if (ZSTD_isError(decompressed_size)) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "ZSTD_decompressDCtx failed: %s\n", ZSTD_getErrorName(decompressed_size));
+        #endif
+        free(output_buffer);
+        if (ZSTD_getErrorCode(decompressed_size) == ZSTD_error_frameParameter_windowTooLarge) {
+            result.length = DECOMPRESS_ERR_WINDOW_TOO_LARGE;
+        } else {
+            result.length = zstd_decompress_error_code(decompressed_size);
+        }
+        return result;
+    }
+    if (decompressed_size != original_len) {
+        free(output_buffer);
+        result.length = DECOMPRESS_ERR_HEADER_MISMATCH;
+        return result;
+    }
+
+    result.buffer = output_buffer;
+    result.length = decompressed_size;
+    return result;
+}
+
+// Streaming zstd compression. The stream is an opaque ZSTD_CCtx that the caller feeds
+// with zstd_stream_compress and releases with zstd_stream_free. Output is a plain zstd
+// frame without the varint length header, since the total length is not known up front;
+// read it back with decompress_data_zstd_stream.
+
+// Creates a compression stream at the given level, or returns NULL if the level is out of range
+void *zstd_stream_create(int level) {
+    if (level < ZSTD_minCLevel() || level > ZSTD_maxCLevel()) {
+        return NULL;
+    }
+    ZSTD_CCtx *cctx = ZSTD_createCCtx();
+    if (cctx == NULL) {
+        return NULL;
+    }
+    if (ZSTD_isError(ZSTD_CCtx_setParameter(cctx, ZSTD_c_compressionLevel, level))) {
+        ZSTD_freeCCtx(cctx);
+        return NULL;
+    }
+    return cctx;
+}
+
+// Bounds the size of each compressed block, so a streaming reader can decode output in
+// small steps. Returns 0 on success, -1 if zstd rejects the size.
+int zstd_stream_set_target_block_size(void *stream, unsigned long bytes) {
+    if (bytes > ZSTD_TARGETCBLOCKSIZE_MAX) {
+        return -1;
+    }
+    size_t res = ZSTD_CCtx_setParameter((ZSTD_CCtx *)stream, ZSTD_c_targetCBlockSize, (int)bytes);
+    return ZSTD_isError(res) ? -1 : 0;
+}
+
+// Feeds input to the stream and returns whatever compressed output zstd produced.
+// mode: 0 = continue (zstd may buffer), 1 = flush all buffered data, 2 = end the frame.
+// On success the buffer is non-NULL even when no output was produced (length 0).
+CompressedData zstd_stream_compress(void *stream, const char *input, unsigned long input_len, int mode) {
+    CompressedData result = {NULL, 0};
+    ZSTD_EndDirective end_op = mode == 2 ? ZSTD_e_end : (mode == 1 ? ZSTD_e_flush : ZSTD_e_continue);
+
+    size_t capacity = ZSTD_compressBound(input_len) + ZSTD_CStreamOutSize();
+    char *output_buffer = (char *)malloc(capacity);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for ZSTD stream compression");
+        return result;
+    }
+
+    ZSTD_inBuffer in = {input, input_len, 0};
+    ZSTD_outBuffer out = {output_buffer, capacity, 0};
+    for (;;) {
+        size_t remaining = ZSTD_compressStream2((ZSTD_CCtx *)stream, &out, &in, end_op);
+        if (ZSTD_isError(remaining)) {
+            #ifdef DEBUG_FUZZING
+            fprintf(stderr, "ZSTD stream compression failed: %s\n", ZSTD_getErrorName(remaining));
+            #endif
+            free(output_buffer);
+            return result;
+        }
+
+        // continue is done once the input is consumed; flush and end once nothing is left to write
+        if (end_op == ZSTD_e_continue ? in.pos == in.size : remaining == 0) {
+            break;
+        }
+
+        if (out.pos == out.size) {
+            char *grown = (char *)realloc(output_buffer, capacity * 2);
+            if (grown == NULL) {
+                perror("Failed to grow ZSTD stream output buffer");
+                free(output_buffer);
+                return result;
+            }
+            output_buffer = grown;
+            capacity *= 2;
+            out.dst = output_buffer;
+            out.size = capacity;
+        }
+    }
+
+    result.buffer = output_buffer;
+    result.length = out.pos;
+    return result;
+}
+
+void zstd_stream_free(void *stream) {
+    ZSTD_freeCCtx((ZSTD_CCtx *)stream);
+}
+
+// Decompresses a complete zstd frame written by the streaming compressor (no varint header).
+// The caller is responsible for freeing the returned buffer
+DecompressedData decompress_data_zstd_stream(const char *input, unsigned long input_len) {
+    DecompressedData result = {NULL, 0};
+
+    ZSTD_DCtx *dctx = ZSTD_createDCtx();
+    if (dctx == NULL) {
+        return result;
+    }
+
+    size_t capacity = ZSTD_DStreamOutSize();
+    char *output_buffer = (char *)malloc(capacity);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for ZSTD stream decompression");
+        ZSTD_freeDCtx(dctx);
+        return result;
+    }
+
+    ZSTD_inBuffer in = {input, input_len, 0};
+    ZSTD_outBuffer out = {output_buffer, capacity, 0};
+    // Stopping early is the input's fault unless an allocation fails
+    unsigned long error = DECOMPRESS_ERR_CORRUPT;
+    size_t ret = 1;
+    while (ret != 0) {
+        ret = ZSTD_decompressStream(dctx, &out, &in);
+        if (ZSTD_isError(ret)) {
+            error = zstd_decompress_error_code(ret);
+            break;
+        }
+        if (ret == 0) {
+            break; // Frame complete
+        }
+        if (out.pos == out.size) {
+            // Same 100MB cap as the one-shot decompressors, reported as corrupt like theirs
+            if (capacity * 2 > 100 * 1024 * 1024) {
+                ret = (size_t)-1;
+                break;
+            }
+            char *grown = (char *)realloc(output_buffer, capacity * 2);
+            if (grown == NULL) {
+                error = DECOMPRESS_ERR_GENERIC;
+                ret = (size_t)-1;
+                break;
+            }
+            output_buffer = grown;
+            capacity *= 2;
+            out.dst = output_buffer;
+            out.size = capacity;
+        } else if (in.pos == in.size) {
+            // Out of input before the end of the frame
+            ret = (size_t)-1;
+            break;
+        }
+    }
+    ZSTD_freeDCtx(dctx);
+
+    if (ret != 0 || in.pos != in.size) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "ZSTD stream decompression failed\n");
+        #endif
+        free(output_buffer);
+        // Covers trailing bytes after a complete frame too
+        result.length = error;
+        return result;
+    }
+
+    result.buffer = output_buffer;
+    result.length = out.pos;
+    return result;
+}
+
+// Incremental zstd decompression, the counterpart of zlib_dstream_* for one zstd frame.
+// The stream is an opaque ZSTD_DCtx.
+
+// Creates a decompression stream, or returns NULL if allocation fails
+void *zstd_dstream_create(void) {
+    return ZSTD_createDCtx();
+}
+
+// Same contract as zlib_dstream_decompress; *finished is set once the frame is complete.
+DecompressedData zstd_dstream_decompress(void *stream, const char *input, unsigned long input_len,
+                                         unsigned long max_output, unsigned long *consumed, int *finished) {
+    DecompressedData result = {NULL, 0};
+    *consumed = 0;
+    *finished = 0;
+
+    char *output_buffer = (char *)malloc(max_output + 1);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for ZSTD stream decompression");
+        return result;
+    }
+
+    ZSTD_inBuffer in = {input, input_len, 0};
+    ZSTD_outBuffer out = {output_buffer, max_output, 0};
+    while (out.pos < out.size) {
+        size_t ret = ZSTD_decompressStream((ZSTD_DCtx *)stream, &out, &in);
+        if (ZSTD_isError(ret)) {
+            #ifdef DEBUG_FUZZING
+            fprintf(stderr, "ZSTD stream decompression failed: %s\n", ZSTD_getErrorName(ret));
+            #endif
+            free(output_buffer);
+            result.length = zstd_decompress_error_code(ret);
+            return result;
+        }
+        if (ret == 0) {
+            *finished = 1;
+            break;
+        }
+        // Input used up and output not full: zstd needs more input to go on
+        if (in.pos == in.size && out.pos < out.size) {
+            break;
+        }
+    }
+
+    *consumed = in.pos;
+    result.buffer = output_buffer;
+    result.length = out.pos;
+    return result;
+}
+
+void zstd_dstream_free(void *stream) {
+    ZSTD_freeDCtx((ZSTD_DCtx *)stream);
+}
+#endif // HAVE_ZSTD
+
+// Range decompression: return bytes [start, start + len) of the original data without
+// materialising the rest. The range is clamped to the original length from the header.
+// zlib and zstd decode through a small scratch buffer until start and stop as soon as
+// len bytes are collected; LZ4 blocks cannot be resumed, so LZ4 decodes only the prefix
+// up to start + len.
+#define RANGE_SCRATCH_SIZE 16384
+
+// Decodes the varint header and clamps the requested range to the original length.
+// Returns the header size, or -1 if the input is malformed or the original length is
+// over the sanity limit.
+static int prepare_range(const char *input, unsigned long input_len,
+                         unsigned long *start, unsigned long *len, unsigned long *original_len) {
+    if (input_len < 1) {
+        return -1;
+    }
+
+    int header_size = decode_varint(input, input_len, original_len);
+    if (header_size < 0) {
+        return -1;
+    }
+    // Only the canonical empty frame may have no payload; the clamped range below is
+    // then empty and the callers decode nothing
+    if ((unsigned long)header_size >= input_len && *original_len != 0) {
+        return -1;
+    }
+
+    // Sanity check on original length (prevent absurdly large allocations)
+    if (*original_len > 100 * 1024 * 1024) { // 100MB limit
+        return -1;
+    }
+
+    if (*start > *original_len) {
+        *start = *original_len;
+    }
+    if (*len > *original_len - *start) {
+        *len = *original_len - *start;
+    }
+    return header_size;
+}
+
+DecompressedData decompress_data_range(const char *input, unsigned long input_len,
+                                       unsigned long start, unsigned long len) {
+    DecompressedData result = {NULL, 0};
+    unsigned long original_len;
+    int header_size = prepare_range(input, input_len, &start, &len, &original_len);
+    if (header_size < 0) {
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    char *output_buffer = (char *)malloc(len + 1);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for range decompression");
+        return result;
+    }
+
+    z_stream strm;
+    memset(&strm, 0, sizeof(strm));
+    if (inflateInit(&strm) != Z_OK) {
+        free(output_buffer);
+        return result;
+    }
+
+    char scratch[RANGE_SCRATCH_SIZE];
+    unsigned long pos = 0;    // Decompressed bytes produced so far
+    unsigned long copied = 0; // Bytes of the range written to output_buffer
+    // Stopping short is the input's fault unless zlib ran out of memory
+    unsigned long error = DECOMPRESS_ERR_CORRUPT;
+    strm.next_in = (Bytef *)(input + header_size);
+    strm.avail_in = (uInt)(input_len - header_size);
+
+    while (copied < len) {
+        // Before the range, decode into scratch without overshooting start;
+        // inside it, decode straight into the output
+        if (pos < start) {
+            unsigned long skip = start - pos;
+            strm.next_out = (Bytef *)scratch;
+            strm.avail_out = (uInt)(skip < RANGE_SCRATCH_SIZE ? skip : RANGE_SCRATCH_SIZE);
+        } else {
+            strm.next_out = (Bytef *)(output_buffer + copied);
+            strm.avail_out = (uInt)(len - copied);
+        }
+
+        unsigned long before = strm.total_out;
+        int res = inflate(&strm, Z_NO_FLUSH);
+        unsigned long produced = strm.total_out - before;
+        if (pos >= start) {
+            copied += produced;
+        }
+        pos += produced;
+
+        // Z_BUF_ERROR here means no progress was possible, i.e. truncated input
+        if (res != Z_OK) {
+            if (res == Z_MEM_ERROR) {
+                error = DECOMPRESS_ERR_GENERIC;
+            }
+            break;
+        }
+    }
+    inflateEnd(&strm);
+
+    if (copied < len) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "Range decompression stopped after %lu of %lu bytes\n", copied, len);
+        #endif
+        free(output_buffer);
+        result.length = error;
+        return result;
+    }
+
+    result.buffer = output_buffer;
+    result.length = len;
+    return result;
+}
+
+#ifdef HAVE_LZ4
+DecompressedData decompress_data_range_lz4(const char *input, unsigned long input_len,
+                                           unsigned long start, unsigned long len) {
+    DecompressedData result = {NULL, 0};
+    unsigned long original_len;
+    int header_size = prepare_range(input, input_len, &start, &len, &original_len);
+    if (header_size < 0) {
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    unsigned long target = start + len;
+    char *output_buffer = (char *)malloc(target + 1);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for LZ4 range decompression");
+        return result;
+    }
+    // Like the zlib and zstd loops, an empty range needs no decoding
+    if (target == 0) {
+        result.buffer = output_buffer;
+        return result;
+    }
+
+    // Stops decoding once target bytes are available
+    int decoded = LZ4_decompress_safe_partial(input + header_size, output_buffer,
+                                              (int)(input_len - header_size), (int)target, (int)target);
+    if (decoded < 0 || (unsigned long)decoded < target) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "LZ4 range decompression failed: %d\n", decoded);
+        #endif
+        free(output_buffer);
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    memmove(output_buffer, output_buffer + start, len);
+    result.buffer = output_buffer;
+    result.length = len;
+    return result;
+}
+#endif // HAVE_LZ4
+
+#ifdef HAVE_ZSTD
+DecompressedData decompress_data_range_zstd(const char *input, unsigned long input_len,
+                                            unsigned long start, unsigned long len) {
+    DecompressedData result = {NULL, 0};
+    unsigned long original_len;
+    int header_size = prepare_range(input, input_len, &start, &len, &original_len);
+    if (header_size < 0) {
+        result.length = DECOMPRESS_ERR_CORRUPT;
+        return result;
+    }
+
+    char *output_buffer = (char *)malloc(len + 1);
+    if (output_buffer == NULL) {
+        perror("Failed to allocate memory for ZSTD range decompression");
+        return result;
+    }
+
+    ZSTD_DCtx *dctx = ZSTD_createDCtx();
+    if (dctx == NULL) {
+        free(output_buffer);
+        return result;
+    }
+
+    char scratch[RANGE_SCRATCH_SIZE];
+    unsigned long pos = 0;
+    unsigned long copied = 0;
+    unsigned long error = DECOMPRESS_ERR_CORRUPT;
+    ZSTD_inBuffer in = {input + header_size, input_len - header_size, 0};
+
+    while (copied < len) {
+        ZSTD_outBuffer out;
+        if (pos < start) {
+            unsigned long skip = start - pos;
+            out = (ZSTD_outBuffer){scratch, skip < RANGE_SCRATCH_SIZE ? skip : RANGE_SCRATCH_SIZE, 0};
+        } else {
+            out = (ZSTD_outBuffer){output_buffer + copied, len - copied, 0};
+        }
+
+        size_t ret = ZSTD_decompressStream(dctx, &out, &in);
+        if (ZSTD_isError(ret)) {
+            error = zstd_decompress_error_code(ret);
+            break;
+        }
+        if (pos >= start) {
+            copied += out.pos;
+        }
+        pos += out.pos;
+
+        // Frame finished, or no input left and nothing more could be flushed
+        if (ret == 0 || (out.pos == 0 && in.pos == in.size)) {
+            break;
+        }
+    }
+    ZSTD_freeDCtx(dctx);
+
+    if (copied < len) {
+        #ifdef DEBUG_FUZZING
+        fprintf(stderr, "ZSTD range decompression stopped after %lu of %lu bytes\n", copied, len);
+        #endif
+        free(output_buffer);
+        result.length = error;
+        return result;
+    }
+
+    result.buffer = output_buffer;
+    result.length = len;
+    return result;
+}
+#endif // HAVE_ZSTD
+
 // Function to free the memory allocated by compress_string
 void free_compressed_data(CompressedData data) {
     if (data.buffer != NULL) {
@@ -0,0 +1,304 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::bytes::{compress_rust_bytes, decompress_rust_data_bytes};
+use crate::error::CompressionError;
+use crate::{compress_rust_string, compress_rust_string_level, decompress_rust_data, zlib_level_range, zlib_version_string};
+#[cfg(feature = "lz4")]
+use crate::{compress_rust_string_lz4, decompress_rust_data_lz4, lz4_version_string, LZ4_MAX_INPUT_SIZE};
+#[cfg(feature = "zstd")]
+use crate::{
+    compress_rust_string_zstd, compress_rust_string_zstd_level, decompress_rust_data_zstd, zstd_level_range, zstd_version_string,
+    ZSTD_MAX_INPUT_SIZE,
+};
+
+/// The compression algorithms backed by the C library.
+///
+/// Every codec produces the same outer layout, `[varint original length][compressed data]`,
+/// but the compressed payloads are not interchangeable: data must be decompressed
+/// with the codec that produced it.
+///
+/// `Lz4` and `Zstd` only exist when the `lz4` and `zstd` cargo features (both on by
+/// default) are enabled; zlib is always available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    Zlib,
+    #[cfg(feature = "lz4")]
+    Lz4,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Codec {
+    /// Number of codecs compiled into this build.
+    pub const COUNT: usize = 1 + cfg!(feature = "lz4") as usize + cfg!(feature = "zstd") as usize;
+
+    /// All codecs compiled into this build, in the order they were added to the crate.
+    pub const ALL: [Codec; Codec::COUNT] = [
+        Codec::Zlib,
+        #[cfg(feature = "lz4")]
+        Codec::Lz4,
+        #[cfg(feature = "zstd")]
+        Codec::Zstd,
+    ];
+
+    /// Short lowercase name, as accepted by the CLI's `--algo` flag.
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Zlib => "zlib",
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => "lz4",
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => "zstd",
+        }
+    }
+
+    /// Parses a codec name such as `"zstd"` (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Codec> {
+        Codec::ALL.into_iter().find(|codec| codec.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Stable one-byte identifier written into tagged blobs. Never reuse a retired id.
+    pub fn id(self) -> u8 {
+        match self {
+            Codec::Zlib => 1,
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => 2,
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => 3,
+        }
+    }
+
+    /// Looks up a codec by the identifier returned from `id`.
+    pub fn from_id(id: u8) -> Option<Codec> {
+        Codec::ALL.into_iter().find(|codec| codec.id() == id)
+    }
+
+    /// Conventional file extension for data written with this codec, without the dot.
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            Codec::Zlib => "zlib",
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => "lz4",
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => "zst",
+        }
+    }
+
+    /// Infers the codec from a path's extension, as written by `file_extension`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Codec> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        Codec::ALL.into_iter().find(|codec| codec.file_extension().eq_ignore_ascii_case(ext))
+    }
+
+    /// Compresses `s` with this codec.
+    pub fn compress(self, s: &str) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            Codec::Zlib => compress_rust_string(s),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => compress_rust_string_lz4(s),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => compress_rust_string_zstd(s),
+        }
+    }
+
+    /// Compression levels this codec accepts, or `None` if it has no level setting (LZ4).
+    pub fn level_range(self) -> Option<RangeInclusive<i32>> {
+        match self {
+            Codec::Zlib => Some(zlib_level_range()),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => None,
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => Some(zstd_level_range()),
+        }
+    }
+
+    /// Largest input, in bytes, this codec can compress.
+    ///
+    /// zlib has no limit of its own, so its bound is the address space.
+    pub fn max_input_size(self) -> usize {
+        match self {
+            Codec::Zlib => usize::MAX,
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => LZ4_MAX_INPUT_SIZE,
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => ZSTD_MAX_INPUT_SIZE,
+        }
+    }
+
+    /// Fails with `InputTooLarge` if an input of `len` bytes is over `max_input_size`, so
+    /// oversized input is rejected before it reaches the C library.
+    pub(crate) fn check_input_size(self, len: usize) -> Result<(), CompressionError> {
+        let max = self.max_input_size();
+        if len > max {
+            return Err(CompressionError::InputTooLarge { len, max });
+        }
+        Ok(())
+    }
+
+    /// Version of the C library behind this codec, as reported by the library linked at
+    /// runtime (which can differ from the headers the crate was built against).
+    pub fn library_version(self) -> &'static str {
+        let version = unsafe {
+            match self {
+                Codec::Zlib => zlib_version_string(),
+                #[cfg(feature = "lz4")]
+                Codec::Lz4 => lz4_version_string(),
+                #[cfg(feature = "zstd")]
+                Codec::Zstd => zstd_version_string(),
+            }
+        };
+        // All three libraries return a pointer to a static, null-terminated ASCII string
+        unsafe { CStr::from_ptr(version) }.to_str().unwrap_or("unknown")
+    }
+
+    /// Compresses `s` with this codec at an explicit level.
+    ///
+    /// Returns `InvalidLevel` if the level is outside `level_range()`, which is every
+    /// level for codecs without one.
+    pub fn compress_level(self, s: &str, level: i32) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            Codec::Zlib => compress_rust_string_level(s, level),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => Err(CompressionError::InvalidLevel { codec: self, level }),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => compress_rust_string_zstd_level(s, level),
+        }
+    }
+
+    /// Decompresses `data` (including its varint header) with this codec.
+    pub fn decompress(self, data: &[u8]) -> Result<String, CompressionError> {
+        match self {
+            Codec::Zlib => decompress_rust_data(data),
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => decompress_rust_data_lz4(data),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => decompress_rust_data_zstd(data),
+        }
+    }
+}
+
+/// The codecs compiled into this build, as a slice that outlives any one call.
+///
+/// The same list as `Codec::ALL`, for callers that want a `&'static [Codec]` rather than a
+/// fixed-size array whose length depends on the enabled features.
+pub fn available_codecs() -> &'static [Codec] {
+    &Codec::ALL
+}
+
+/// Compresses `s` into a reference-counted buffer for fan-out to many readers.
+///
+/// Cloning the returned `Arc<[u8]>` only bumps a reference count, so one compressed
+/// blob can be handed to any number of threads or tasks without copying it.
+pub fn compress_shared(codec: Codec, s: &str) -> Result<Arc<[u8]>, CompressionError> {
+    codec.compress(s).map(Arc::from)
+}
+
+/// Known input for `self_test`: repetitive enough to exercise matches, with multi-byte
+/// UTF-8 to catch length/encoding slips.
+const SELF_TEST_TEXT: &str = "self-test: Grüße, 你好, 🚀 — self-test: Grüße, 你好, 🚀 — 0123456789";
+
+/// Checks that `codec` works end to end: compresses a fixed string and fixed binary
+/// data, decompresses both and compares them with the input.
+///
+/// Meant for startup and health checks. It catches a C library that links but
+/// misbehaves, e.g. an incompatible shared library version picked up at run time.
+/// A library that is missing altogether never gets this far: the dynamic loader
+/// refuses to start the process ("error while loading shared libraries"), and a codec
+/// whose cargo feature is disabled has no `Codec` variant to test.
+///
+/// # Returns
+/// * `Ok(())` if both round trips returned the original data.
+/// * `Err(CompressionError::SelfTestFailed)` if a round trip returned different data.
+/// * `Err(CompressionError)` from the compressor or decompressor if either failed.
+pub fn self_test(codec: Codec) -> Result<(), CompressionError> {
+    let text = codec.decompress(&codec.compress(SELF_TEST_TEXT)?)?;
+    if text != SELF_TEST_TEXT {
+        return Err(CompressionError::SelfTestFailed(codec));
+    }
+
+    // Every byte value, including the nulls the &str path cannot carry
+    let binary: Vec<u8> = (0..=255u8).cycle().take(1024).collect();
+    if decompress_rust_data_bytes(&compress_rust_bytes(&binary, codec)?, codec)? != binary {
+        return Err(CompressionError::SelfTestFailed(codec));
+    }
+    Ok(())
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Codec::Zlib => "zlib",
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => "LZ4",
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => "ZSTD",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_name_round_trip() {
+        for codec in Codec::ALL {
+            assert_eq!(Codec::from_name(codec.name()), Some(codec));
+            assert_eq!(Codec::from_name(&codec.name().to_uppercase()), Some(codec));
+        }
+        assert_eq!(Codec::from_name("brotli"), None);
+    }
+
+    #[test]
+    fn test_all_matches_enabled_features() {
+        assert_eq!(Codec::ALL.len(), Codec::COUNT);
+        assert_eq!(Codec::from_name("lz4").is_some(), cfg!(feature = "lz4"));
+        assert_eq!(Codec::from_name("zstd").is_some(), cfg!(feature = "zstd"));
+        // Ids are stable whichever codecs are compiled in
+        assert_eq!(Codec::from_id(1), Some(Codec::Zlib));
+    }
+
+    #[test]
+    fn test_self_test_all_codecs() {
+        for codec in Codec::ALL {
+            assert_eq!(self_test(codec), Ok(()), "{} self-test should pass", codec);
+        }
+    }
+
+    #[test]
+    fn test_compress_shared_across_threads() {
+        let original_data = "Broadcast this compressed blob to every consumer. ".repeat(20);
+
+        for codec in Codec::ALL {
+            let shared = compress_shared(codec, &original_data).expect("Shared compression should work");
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let blob = Arc::clone(&shared);
+                    std::thread::spawn(move || codec.decompress(&blob))
+                })
+                .collect();
+
+            for handle in handles {
+                let decompressed = handle.join().expect("Consumer thread panicked");
+                assert_eq!(decompressed.as_deref(), Ok(original_data.as_str()), "{} consumer should see the original data", codec);
+            }
+            assert_eq!(Arc::strong_count(&shared), 1, "All consumer clones should have been dropped");
+        }
+    }
+
+    #[test]
+    fn test_codec_from_path() {
+        #[cfg(feature = "zstd")]
+        assert_eq!(Codec::from_path("compressed_output.zst"), Some(Codec::Zstd));
+        #[cfg(feature = "lz4")]
+        assert_eq!(Codec::from_path("dir/compressed_output.lz4"), Some(Codec::Lz4));
+        assert_eq!(Codec::from_path("compressed_output.zlib"), Some(Codec::Zlib));
+        assert_eq!(Codec::from_path("compressed_output.bin"), None);
+        assert_eq!(Codec::from_path("compressed_output"), None);
+    }
+}
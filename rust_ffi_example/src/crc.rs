@@ -0,0 +1,92 @@
+//! End-to-end content checks: a CRC-32 over the original data, not the compressed bytes.
+//!
+//! A checksum of the compressed bytes only proves the blob arrived intact. The content
+//! CRC is computed before compression and checked after decompression, so it also
+//! catches a wrong codec, a decompressor bug, or data altered after decoding.
+
+use std::os::raw::c_char;
+
+use libc::c_ulong;
+
+use crate::codec::Codec;
+use crate::content_crc32;
+use crate::error::CompressionError;
+
+/// CRC-32 (IEEE polynomial, as used by gzip) of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    // The full length is passed through: `content_crc32` hashes inputs of 4 GiB or more
+    // in chunks rather than truncating the length to zlib's 32-bit `uInt`.
+    unsafe { content_crc32(data.as_ptr() as *const c_char, data.len() as c_ulong) as u32 }
+}
+
+fn verify_content_crc(content: &str, expected_crc: u32) -> Result<(), CompressionError> {
+    let actual = crc32(content.as_bytes());
+    if actual != expected_crc {
+        return Err(CompressionError::ContentCrcMismatch { expected: expected_crc, actual });
+    }
+    Ok(())
+}
+
+/// Compresses `s` and returns the compressed bytes with the CRC-32 of `s` itself.
+///
+/// Send the CRC alongside the blob and check it with `decompress_verify_content_crc`.
+pub fn compress_with_content_crc(s: &str, codec: Codec) -> Result<(Vec<u8>, u32), CompressionError> {
+    let compressed = codec.compress(s)?;
+    Ok((compressed, crc32(s.as_bytes())))
+}
+
+/// Decompresses `data` and checks the result against the CRC from `compress_with_content_crc`.
+///
+/// # Returns
+/// * `Ok(String)` with the original content.
+/// * `Err(CompressionError::ContentCrcMismatch)` if the content differs from what was compressed.
+/// * `Err(CompressionError)` if decompression fails.
+pub fn decompress_verify_content_crc(data: &[u8], codec: Codec, expected_crc: u32) -> Result<String, CompressionError> {
+    let content = codec.decompress(data)?;
+    verify_content_crc(&content, expected_crc)?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_known_value() {
+        // Standard CRC-32 check value
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_crc32_over_4_gib_uses_full_length() {
+        // Truncating the length to 32 bits would hash only the first 3 bytes. The zeroed
+        // buffer is only ever read, so it costs address space rather than resident memory.
+        let data = vec![0u8; (1 << 32) + 3];
+        assert_ne!(crc32(&data), crc32(&data[..3]));
+    }
+
+    #[test]
+    fn test_content_crc_round_trip() {
+        let original_data = "Validate what the far end actually decoded. ".repeat(10);
+        for codec in Codec::ALL {
+            let (compressed, crc) = compress_with_content_crc(&original_data, codec).expect("Compression should work");
+            assert_eq!(decompress_verify_content_crc(&compressed, codec, crc), Ok(original_data.clone()));
+        }
+    }
+
+    #[test]
+    fn test_tampered_content_fails_crc() {
+        let (_, crc) = compress_with_content_crc("transfer 100 coins to alice", Codec::Zlib).unwrap();
+
+        // Simulated tampering after decoding: same length, one byte changed
+        let tampered = "transfer 900 coins to alice";
+        assert!(matches!(verify_content_crc(tampered, crc), Err(CompressionError::ContentCrcMismatch { expected, .. }) if expected == crc));
+
+        // A well-formed blob carrying different content passes decompression but not the CRC
+        let (substituted, _) = compress_with_content_crc(tampered, Codec::Zlib).unwrap();
+        let result = decompress_verify_content_crc(&substituted, Codec::Zlib, crc);
+        assert_eq!(result, Err(CompressionError::ContentCrcMismatch { expected: crc, actual: crc32(tampered.as_bytes()) }));
+    }
+}
@@ -0,0 +1,282 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::codec::Codec;
+
+/// Errors returned by the safe Rust wrappers around the C compression library.
+///
+/// The C functions only signal failure by returning a null buffer, so most
+/// variants describe which stage of the wrapper detected the problem rather
+/// than the exact zlib/LZ4/zstd error code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressionError {
+    /// The input contained an interior null byte and could not be converted to a `CString`.
+    NullByte,
+    /// The input slice was empty.
+    EmptyInput,
+    /// The input was too short to hold a varint header and a compressed payload.
+    InputTooSmall(Codec),
+    /// The input is longer than the format or codec can represent.
+    InputTooLarge {
+        /// Length of the input in bytes.
+        len: usize,
+        /// Largest length accepted.
+        max: usize,
+    },
+    /// The requested compression level is outside the codec's supported range.
+    InvalidLevel { codec: Codec, level: i32 },
+    /// A zstd target block size outside the range zstd accepts.
+    InvalidBlockSize(usize),
+    /// A zstd window log limit outside `zstd_window_log_range()`.
+    InvalidWindowLog(u32),
+    /// The C library returned a null buffer while compressing.
+    CompressionFailed(Codec),
+    /// The C library returned a null buffer while decompressing, for a reason other than
+    /// the input (e.g. an allocation failure).
+    DecompressionFailed(Codec),
+    /// The input is not valid data for the codec: a malformed header, a corrupt or
+    /// truncated payload, or data written by a different codec. A header claiming more
+    /// than the 100 MB the C decompressors will allocate is reported this way too.
+    CorruptData(Codec),
+    /// The varint header disagrees with the size the payload decodes to, e.g. a header
+    /// of 0 followed by a payload that inflates to data.
+    HeaderMismatch(Codec),
+    /// Decompressing would need more memory than the caller allowed.
+    ResourceLimitExceeded {
+        /// Peak bytes the call would allocate or keep resident.
+        needed: usize,
+        /// Budget passed by the caller.
+        limit: usize,
+    },
+    /// A zstd frame needs a larger decoding window than the caller allowed.
+    WindowTooLarge {
+        /// The caller's limit, as a power of two.
+        max_window_log: u32,
+    },
+    /// `self_test` round-tripped its known input and got different data back.
+    SelfTestFailed(Codec),
+    /// The decompressed content does not match the CRC-32 recorded at compression time.
+    ContentCrcMismatch { expected: u32, actual: u32 },
+    /// The decompressed bytes were not valid UTF-8.
+    InvalidUtf8(Codec),
+    /// `encode_varint` reported an out-of-range byte count.
+    VarintEncodeFailed,
+    /// The varint was malformed, overflowed 64 bits, or ran past the end of the input.
+    InvalidVarint,
+    /// A frame header claimed more bytes than remain in the input.
+    TruncatedFrame {
+        /// Byte offset of the frame header within the input.
+        offset: usize,
+        /// Payload length declared by the frame header.
+        needed: usize,
+        /// Bytes actually available after the frame header.
+        available: usize,
+    },
+    /// The container was written with a format version this release cannot read.
+    UnsupportedVersion {
+        /// Version byte found in the data.
+        found: u8,
+        /// Newest version this release understands.
+        max_supported: u8,
+    },
+    /// A tagged blob names a codec this release does not know.
+    UnknownCodecTag(u8),
+    /// The leading bytes match no codec compiled into this build.
+    UnrecognizedFormat,
+    /// Reading or writing compressed data through the filesystem failed.
+    Io(io::ErrorKind),
+}
+
+impl CompressionError {
+    /// Returns `true` if the error was caused by the input, and `false` if it was an
+    /// internal failure.
+    ///
+    /// Services can map `true` to a 4xx response and `false` to a 5xx. The C library
+    /// reports payloads it rejected as `CorruptData`, which is a client error; any other
+    /// null buffer counts as internal, since that is also what an allocation failure
+    /// looks like.
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            CompressionError::NullByte
+            | CompressionError::EmptyInput
+            | CompressionError::InputTooSmall(_)
+            | CompressionError::InputTooLarge { .. }
+            | CompressionError::InvalidLevel { .. }
+            | CompressionError::InvalidBlockSize(_)
+            | CompressionError::InvalidWindowLog(_)
+            | CompressionError::HeaderMismatch(_)
+            | CompressionError::CorruptData(_)
+            | CompressionError::WindowTooLarge { .. }
+            | CompressionError::ContentCrcMismatch { .. }
+            | CompressionError::InvalidUtf8(_)
+            | CompressionError::InvalidVarint
+            | CompressionError::TruncatedFrame { .. }
+            | CompressionError::UnsupportedVersion { .. }
+            | CompressionError::UnknownCodecTag(_)
+            | CompressionError::UnrecognizedFormat => true,
+            CompressionError::CompressionFailed(_)
+            | CompressionError::DecompressionFailed(_)
+            | CompressionError::ResourceLimitExceeded { .. }
+            | CompressionError::SelfTestFailed(_)
+            | CompressionError::VarintEncodeFailed
+            | CompressionError::Io(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionError::NullByte => {
+                write!(f, "Failed to create CString, input might contain null bytes")
+            }
+            CompressionError::EmptyInput => write!(f, "Empty input data"),
+            CompressionError::InputTooSmall(codec) => {
+                write!(f, "Input too small for valid {} compressed data", codec)
+            }
+            CompressionError::InputTooLarge { len, max } => {
+                write!(f, "Input of {} bytes exceeds the maximum of {} bytes", len, max)
+            }
+            CompressionError::InvalidLevel { codec, level } => {
+                write!(f, "Compression level {} is not supported by {}", level, codec)
+            }
+            CompressionError::InvalidBlockSize(bytes) => {
+                write!(f, "Target block size of {} bytes is not supported by ZSTD", bytes)
+            }
+            CompressionError::InvalidWindowLog(log) => {
+                write!(f, "Window log {} is not supported by ZSTD", log)
+            }
+            CompressionError::CompressionFailed(codec) => {
+                write!(f, "{} compression failed in C library (null buffer returned)", codec)
+            }
+            CompressionError::DecompressionFailed(codec) => {
+                write!(f, "{} decompression failed in C library (null buffer returned)", codec)
+            }
+            CompressionError::HeaderMismatch(codec) => {
+                write!(f, "{} payload size does not match the length in its header", codec)
+            }
+            CompressionError::CorruptData(codec) => {
+                write!(f, "Input is not valid {} data (corrupt, truncated, or from another codec)", codec)
+            }
+            CompressionError::ResourceLimitExceeded { needed, limit } => write!(
+                f,
+                "Decompression would need {} bytes of memory, over the limit of {}",
+                needed, limit
+            ),
+            CompressionError::WindowTooLarge { max_window_log } => {
+                write!(f, "ZSTD frame needs a window larger than the limit of 2^{} bytes", max_window_log)
+            }
+            CompressionError::SelfTestFailed(codec) => {
+                write!(f, "{} self-test failed: round trip did not return the original data", codec)
+            }
+            CompressionError::ContentCrcMismatch { expected, actual } => write!(
+                f,
+                "Decompressed content CRC-32 {:08x} does not match expected {:08x}",
+                actual, expected
+            ),
+            CompressionError::InvalidUtf8(codec) => {
+                write!(f, "{} decompressed data is not valid UTF-8", codec)
+            }
+            CompressionError::VarintEncodeFailed => write!(f, "Invalid bytes written by encode_varint"),
+            CompressionError::InvalidVarint => write!(f, "Failed to decode varint"),
+            CompressionError::TruncatedFrame { offset, needed, available } => write!(
+                f,
+                "Truncated frame at offset {}: header declares {} bytes but only {} remain",
+                offset, needed, available
+            ),
+            CompressionError::UnsupportedVersion { found, max_supported } => write!(
+                f,
+                "Unsupported format version {} (this release reads up to version {}); upgrade to read this data",
+                found, max_supported
+            ),
+            CompressionError::UnknownCodecTag(tag) => write!(f, "Unknown codec tag {}", tag),
+            CompressionError::UnrecognizedFormat => write!(f, "Input does not start like data from any enabled codec"),
+            CompressionError::Io(kind) => write!(f, "I/O error: {}", kind),
+        }
+    }
+}
+
+impl Error for CompressionError {}
+
+impl From<io::Error> for CompressionError {
+    fn from(e: io::Error) -> Self {
+        CompressionError::Io(e.kind())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::{compress_rust_bytes, decompress_rust_data_bytes};
+    use crate::{decode_varint_rust, encode_varint_rust};
+
+    #[test]
+    fn test_is_client_error_classification() {
+        let client_errors = [
+            CompressionError::NullByte,
+            CompressionError::EmptyInput,
+            CompressionError::InputTooSmall(Codec::Zlib),
+            CompressionError::InputTooLarge { len: 5000, max: 4096 },
+            CompressionError::InvalidLevel { codec: Codec::Zlib, level: 99 },
+            CompressionError::InvalidBlockSize(1 << 20),
+            CompressionError::InvalidWindowLog(99),
+            CompressionError::HeaderMismatch(Codec::Zlib),
+            CompressionError::CorruptData(Codec::Zlib),
+            CompressionError::WindowTooLarge { max_window_log: 10 },
+            CompressionError::ContentCrcMismatch { expected: 1, actual: 2 },
+            CompressionError::InvalidUtf8(Codec::Zlib),
+            CompressionError::InvalidVarint,
+            CompressionError::TruncatedFrame { offset: 0, needed: 10, available: 3 },
+            CompressionError::UnsupportedVersion { found: 2, max_supported: 1 },
+            CompressionError::UnknownCodecTag(0xEE),
+            CompressionError::UnrecognizedFormat,
+        ];
+        for error in client_errors {
+            assert!(error.is_client_error(), "{:?} should be a client error", error);
+        }
+
+        let internal_errors = [
+            CompressionError::CompressionFailed(Codec::Zlib),
+            CompressionError::DecompressionFailed(Codec::Zlib),
+            CompressionError::ResourceLimitExceeded { needed: 1 << 30, limit: 1 << 20 },
+            CompressionError::SelfTestFailed(Codec::Zlib),
+            CompressionError::VarintEncodeFailed,
+            CompressionError::Io(io::ErrorKind::PermissionDenied),
+        ];
+        for error in internal_errors {
+            assert!(!error.is_client_error(), "{:?} should be an internal error", error);
+        }
+    }
+
+    #[test]
+    fn test_bad_input_from_c_layer_is_client_error() {
+        let original = "Client data that will be mangled on the way in. ".repeat(20);
+
+        for codec in Codec::ALL {
+            let blob = compress_rust_bytes(original.as_bytes(), codec).unwrap();
+            let (_, header_len) = decode_varint_rust(&blob).unwrap();
+
+            let mut corrupt = blob.clone();
+            for byte in &mut corrupt[header_len..] {
+                *byte ^= 0x5A;
+            }
+            let error = decompress_rust_data_bytes(&corrupt, codec).expect_err("A corrupt payload should not decompress");
+            assert_eq!(error, CompressionError::CorruptData(codec), "{} corrupt payload", codec);
+            assert!(error.is_client_error());
+
+            for other in Codec::ALL.into_iter().filter(|&other| other != codec) {
+                let error = decompress_rust_data_bytes(&blob, other).expect_err("Another codec's blob should not decompress");
+                assert_eq!(error, CompressionError::CorruptData(other), "{} blob fed to {}", codec, other);
+                assert!(error.is_client_error());
+            }
+
+            // A header claiming 200 MB, over the C layer's 100 MB allocation limit
+            let mut oversized = encode_varint_rust(200 << 20).unwrap();
+            oversized.extend_from_slice(&blob[header_len..]);
+            let error = decompress_rust_data_bytes(&oversized, codec).expect_err("A 200 MB header should be refused");
+            assert_eq!(error, CompressionError::CorruptData(codec), "{} oversized header", codec);
+            assert!(error.is_client_error());
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Compression between raw Unix file descriptors, for `fork`/`exec` pipelines.
+
+use std::io;
+use std::os::raw::c_void;
+use std::os::unix::io::RawFd;
+
+use crate::bytes::compress_rust_bytes;
+use crate::codec::Codec;
+use crate::error::CompressionError;
+#[cfg(feature = "zstd")]
+use crate::zstd_stream::ZstdStreamCompressor;
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reads up to `buf.len()` bytes, retrying on `EINTR`. Returns 0 at end of input.
+fn read_fd(fd: RawFd, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut c_void, buf.len()) };
+        if n >= 0 {
+            return Ok(n as usize);
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Writes all of `data`, retrying on `EINTR` and continuing after short writes.
+fn write_all_fd(fd: RawFd, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        let n = unsafe { libc::write(fd, data.as_ptr() as *const c_void, data.len()) };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if n == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        data = &data[n as usize..];
+    }
+    Ok(())
+}
+
+/// Compresses everything readable from `input_fd` and writes the result to `output_fd`.
+///
+/// zstd streams: input is compressed chunk by chunk with `ZstdStreamCompressor` and
+/// output is written as it is produced, so memory use stays bounded. The output is a
+/// plain zstd frame; read it back with `decompress_zstd_stream`.
+///
+/// zlib and LZ4 store the original length in front of the payload, so their input is
+/// read to the end first and written as one `[varint original length][payload]` blob,
+/// readable with `decompress_rust_data_bytes`.
+///
+/// Both descriptors are borrowed: they are not closed, and `output_fd` is not synced.
+///
+/// # Returns
+/// * `Ok(())` once the input reached end of file and all output was written.
+/// * `Err(CompressionError)` if reading, writing or compression fails.
+pub fn compress_fd(input_fd: RawFd, output_fd: RawFd, codec: Codec) -> Result<(), CompressionError> {
+    let mut buf = vec![0u8; READ_CHUNK_SIZE];
+
+    #[cfg(feature = "zstd")]
+    if codec == Codec::Zstd {
+        // Same level as compress_rust_string_zstd
+        let mut compressor = ZstdStreamCompressor::new(1)?;
+        loop {
+            let n = read_fd(input_fd, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            write_all_fd(output_fd, &compressor.write(&buf[..n])?)?;
+        }
+        write_all_fd(output_fd, &compressor.finish()?)?;
+        return Ok(());
+    }
+
+    let mut input = Vec::new();
+    loop {
+        let n = read_fd(input_fd, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        input.extend_from_slice(&buf[..n]);
+    }
+    write_all_fd(output_fd, &compress_rust_bytes(&input, codec)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    use std::thread;
+
+    use crate::bytes::decompress_rust_data_bytes;
+    #[cfg(feature = "zstd")]
+    use crate::zstd_stream::decompress_zstd_stream;
+
+    fn pipe() -> (File, File) {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0, "pipe() failed");
+        unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) }
+    }
+
+    #[test]
+    fn test_compress_fd_through_pipes() {
+        // Larger than a pipe buffer, so both sides have to make progress concurrently
+        let data: Vec<u8> = (0..200_000u32).flat_map(|i| format!("line {}\n", i % 1000).into_bytes()).collect();
+
+        for codec in Codec::ALL {
+            let (input_read, mut input_write) = pipe();
+            let (mut output_read, output_write) = pipe();
+
+            let feeder = {
+                let data = data.clone();
+                thread::spawn(move || input_write.write_all(&data)) // Dropping closes the write end
+            };
+            let collector = thread::spawn(move || {
+                let mut out = Vec::new();
+                output_read.read_to_end(&mut out).map(|_| out)
+            });
+
+            compress_fd(input_read.as_raw_fd(), output_write.as_raw_fd(), codec).expect("compress_fd should work");
+            drop(output_write);
+
+            feeder.join().unwrap().expect("Writing the input failed");
+            let compressed = collector.join().unwrap().expect("Reading the output failed");
+
+            let decompressed = match codec {
+                #[cfg(feature = "zstd")]
+                Codec::Zstd => decompress_zstd_stream(&compressed),
+                _ => decompress_rust_data_bytes(&compressed, codec),
+            };
+            assert_eq!(decompressed.as_deref(), Ok(&data[..]), "{} pipe round trip should preserve the data", codec);
+        }
+    }
+
+    #[test]
+    fn test_compress_fd_bad_descriptor() {
+        assert_eq!(compress_fd(-1, -1, Codec::Zlib), Err(CompressionError::Io(io::Error::from_raw_os_error(libc::EBADF).kind())));
+    }
+}
@@ -0,0 +1,102 @@
+//! The standard layout with a fixed-width length header, for length-prefixed protocols
+//! that cannot parse varints.
+//!
+//! Layout of a fixed-prefix blob:
+//!
+//! ```text
+//! [original length, u32 big-endian][compressed data]
+//! ```
+//!
+//! The compressed data is the same payload the varint layout carries; only the header
+//! differs, so inputs longer than `u32::MAX` bytes cannot be represented.
+
+use crate::codec::Codec;
+use crate::error::CompressionError;
+use crate::{decode_varint_rust, encode_varint_rust};
+
+const PREFIX_LEN: usize = 4;
+
+/// The big-endian length header for an input of `len` bytes.
+fn length_prefix(len: usize) -> Result<[u8; PREFIX_LEN], CompressionError> {
+    u32::try_from(len)
+        .map(u32::to_be_bytes)
+        .map_err(|_| CompressionError::InputTooLarge { len, max: u32::MAX as usize })
+}
+
+/// Compresses `s` with `codec` behind a 4-byte big-endian original-length header
+/// instead of the usual varint.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` containing the fixed-prefix blob.
+/// * `Err(CompressionError::InputTooLarge)` if `s` is longer than `u32::MAX` bytes.
+/// * `Err(CompressionError)` if compression fails.
+pub fn compress_fixed_prefix(s: &str, codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    let prefix = length_prefix(s.len())?;
+    let blob = codec.compress(s)?;
+    let (_, header_len) = decode_varint_rust(&blob)?;
+
+    let mut out = Vec::with_capacity(PREFIX_LEN + blob.len() - header_len);
+    out.extend_from_slice(&prefix);
+    out.extend_from_slice(&blob[header_len..]);
+    Ok(out)
+}
+
+/// Decompresses a blob produced by `compress_fixed_prefix` with the same codec.
+///
+/// # Returns
+/// * `Ok(String)` with the original string.
+/// * `Err(CompressionError::InputTooSmall)` if `data` is shorter than the 4-byte header.
+/// * `Err(CompressionError)` if the payload fails to decompress or disagrees with the header.
+pub fn decompress_fixed_prefix(data: &[u8], codec: Codec) -> Result<String, CompressionError> {
+    if data.is_empty() {
+        return Err(CompressionError::EmptyInput);
+    }
+    if data.len() < PREFIX_LEN {
+        return Err(CompressionError::InputTooSmall(codec));
+    }
+    let (prefix, payload) = data.split_at(PREFIX_LEN);
+    let original_len = u32::from_be_bytes(prefix.try_into().expect("prefix is PREFIX_LEN bytes"));
+
+    // Rebuild the varint layout the codecs read
+    let mut blob = encode_varint_rust(u64::from(original_len))?;
+    blob.extend_from_slice(payload);
+    codec.decompress(&blob)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_prefix_round_trip_all_codecs() {
+        let original_data = "Length-prefixed protocols want four bytes. ".repeat(12);
+        let expected_prefix = (original_data.len() as u32).to_be_bytes();
+
+        for codec in Codec::ALL {
+            let blob = compress_fixed_prefix(&original_data, codec).expect("Fixed-prefix compression should work");
+            assert_eq!(blob[..4], expected_prefix, "{} blob should start with the big-endian length", codec);
+            assert_eq!(&blob[4..], &codec.compress(&original_data).unwrap()[2..], "{} payload should match the varint layout", codec);
+            assert_eq!(decompress_fixed_prefix(&blob, codec), Ok(original_data.clone()), "{} fixed-prefix round trip", codec);
+        }
+    }
+
+    #[test]
+    fn test_fixed_prefix_errors() {
+        assert_eq!(length_prefix(0x0102_0304), Ok([0x01, 0x02, 0x03, 0x04]));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            length_prefix(u32::MAX as usize + 1),
+            Err(CompressionError::InputTooLarge { len: u32::MAX as usize + 1, max: u32::MAX as usize })
+        );
+
+        assert_eq!(decompress_fixed_prefix(&[], Codec::Zlib), Err(CompressionError::EmptyInput));
+        assert_eq!(decompress_fixed_prefix(&[0, 0, 1], Codec::Zlib), Err(CompressionError::InputTooSmall(Codec::Zlib)));
+        // A zero length and no payload is the empty frame
+        assert_eq!(decompress_fixed_prefix(&[0, 0, 0, 0], Codec::Zlib), Ok(String::new()));
+
+        // A header that disagrees with the payload is caught by the codec
+        let mut blob = compress_fixed_prefix("twelve bytes", Codec::Zlib).unwrap();
+        blob[3] = 5;
+        assert_eq!(decompress_fixed_prefix(&blob, Codec::Zlib), Err(CompressionError::HeaderMismatch(Codec::Zlib)));
+    }
+}
@@ -0,0 +1,236 @@
+//! A simple multi-frame container for concatenating independently compressed chunks.
+//!
+//! Layout of a framed blob:
+//!
+//! ```text
+//! [version][varint frame length][frame] [varint frame length][frame] ...
+//! ```
+//!
+//! The leading byte is the container format version (see `FORMAT_VERSION`). Each frame
+//! is a complete single-codec blob as produced by e.g. `compress_rust_string`
+//! (`[varint original length][compressed data]`). Storing the frame length up front
+//! lets readers skip or count frames without decompressing them.
+
+use crate::codec::Codec;
+use crate::error::CompressionError;
+use crate::version::{strip_version, FORMAT_VERSION};
+use crate::{decode_varint_rust, decompress_range, encode_varint_rust};
+
+/// Compresses each chunk into its own frame and concatenates the frames.
+///
+/// # Arguments
+/// * `chunks`: The strings to compress, one frame per chunk.
+/// * `codec`: The codec used for every frame.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` containing the framed blob.
+/// * `Err(CompressionError)` if any chunk fails to compress.
+pub fn compress_framed(chunks: &[&str], codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    let mut out = vec![FORMAT_VERSION];
+    for chunk in chunks {
+        let frame = codec.compress(chunk)?;
+        out.extend_from_slice(&encode_varint_rust(frame.len() as u64)?);
+        out.extend_from_slice(&frame);
+    }
+    Ok(out)
+}
+
+/// Iterates over the frame payloads of a framed blob without decompressing them.
+///
+/// Yields `(offset, frame)` pairs, where `offset` is the position of the frame's
+/// length header within the blob. Iteration stops after the first error.
+struct Frames<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<(usize, &'a [u8]), CompressionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.data.len() {
+            return None;
+        }
+        let offset = self.pos;
+        // Any error ends the walk; there is no reliable way to resync after a bad header.
+        self.pos = self.data.len();
+
+        let (frame_len, header_len) = match decode_varint_rust(&self.data[offset..]) {
+            Ok(decoded) => decoded,
+            Err(e) => return Some(Err(e)),
+        };
+        let start = offset + header_len;
+        let available = self.data.len() - start;
+        if frame_len > available as u64 {
+            return Some(Err(CompressionError::TruncatedFrame {
+                offset,
+                needed: frame_len as usize,
+                available,
+            }));
+        }
+
+        let end = start + frame_len as usize;
+        self.pos = end;
+        Some(Ok((offset, &self.data[start..end])))
+    }
+}
+
+/// Checks the version byte and returns an iterator over the frames that follow it.
+fn frames(data: &[u8]) -> Result<Frames<'_>, CompressionError> {
+    strip_version(data)?;
+    Ok(Frames { data, pos: 1 })
+}
+
+/// Counts the frames in a framed blob by walking the frame length headers.
+///
+/// No frame is decompressed, so this is cheap enough to call before
+/// `decompress_framed` to size the output up front.
+///
+/// # Returns
+/// * `Ok(usize)` with the number of complete frames.
+/// * `Err(CompressionError)` if the version is unsupported, a frame header is malformed
+///   or a frame is truncated.
+pub fn frame_count(data: &[u8]) -> Result<usize, CompressionError> {
+    frames(data)?.try_fold(0, |count, frame| frame.map(|_| count + 1))
+}
+
+/// Decompresses every frame in a framed blob.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` with one entry per frame, in order.
+/// * `Err(CompressionError)` if the framing is invalid or any frame fails to decompress.
+pub fn decompress_framed(data: &[u8], codec: Codec) -> Result<Vec<String>, CompressionError> {
+    let mut out = Vec::with_capacity(frame_count(data)?);
+    for frame in frames(data)? {
+        let (_, frame) = frame?;
+        out.push(codec.decompress(frame)?);
+    }
+    Ok(out)
+}
+
+/// Decompresses bytes `start..start + len` of the concatenated frame contents.
+///
+/// Each frame's varint header records its original length, so frames that lie
+/// entirely outside the range are skipped without being decompressed, and frames
+/// overlapping it are decoded only as far as needed (see `decompress_range`).
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the requested bytes, clamped to the end of the last frame.
+/// * `Err(CompressionError)` if the framing is invalid or an overlapping frame fails to decompress.
+pub fn decompress_framed_range(data: &[u8], codec: Codec, start: usize, len: usize) -> Result<Vec<u8>, CompressionError> {
+    let end = start.saturating_add(len);
+    let mut out = Vec::new();
+    let mut frame_start = 0usize;
+    for frame in frames(data)? {
+        if frame_start >= end {
+            break;
+        }
+        let (_, frame) = frame?;
+        let (frame_len, _) = decode_varint_rust(frame)?;
+        let frame_end = frame_start.saturating_add(frame_len as usize);
+
+        if frame_end > start {
+            let local_start = start.saturating_sub(frame_start);
+            let local_len = end.min(frame_end) - frame_start - local_start;
+            out.extend_from_slice(&decompress_range(frame, codec, local_start, local_len)?);
+        }
+        frame_start = frame_end;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHUNKS: [&str; 5] = [
+        "first frame",
+        "",
+        "third frame with a bit more text in it",
+        "🦀 Rust FFI 🦀",
+        "fifth frame fifth frame fifth frame",
+    ];
+
+    #[test]
+    fn test_frame_count_five_frames() {
+        for codec in Codec::ALL {
+            let blob = compress_framed(&CHUNKS, codec).expect("Framed compression should work");
+            assert_eq!(frame_count(&blob), Ok(5), "{} framed blob should contain five frames", codec);
+        }
+    }
+
+    #[test]
+    fn test_frame_count_no_frames() {
+        let blob = compress_framed(&[], Codec::Zlib).expect("Framed compression should work");
+        assert_eq!(blob, [FORMAT_VERSION], "A blob without frames is just the version byte");
+        assert_eq!(frame_count(&blob), Ok(0), "A blob without frames contains no frames");
+        assert_eq!(frame_count(&[]), Err(CompressionError::EmptyInput), "The version byte is required");
+    }
+
+    #[test]
+    fn test_framed_future_version_rejected() {
+        let mut blob = compress_framed(&CHUNKS, Codec::Zlib).expect("Framed compression should work");
+        blob[0] = FORMAT_VERSION + 1;
+
+        let expected = CompressionError::UnsupportedVersion { found: FORMAT_VERSION + 1, max_supported: FORMAT_VERSION };
+        assert_eq!(frame_count(&blob), Err(expected.clone()));
+        assert_eq!(decompress_framed(&blob, Codec::Zlib), Err(expected));
+    }
+
+    #[test]
+    fn test_frame_count_truncated_last_frame() {
+        let mut blob = compress_framed(&CHUNKS, Codec::Zlib).expect("Framed compression should work");
+        blob.truncate(blob.len() - 3);
+
+        match frame_count(&blob) {
+            Err(CompressionError::TruncatedFrame { needed, available, .. }) => {
+                assert_eq!(needed - available, 3, "Truncation should be reported exactly");
+            }
+            other => panic!("Truncated last frame should fail with TruncatedFrame, got {:?}", other),
+        }
+        assert!(decompress_framed(&blob, Codec::Zlib).is_err(), "Decompressing a truncated blob should fail");
+    }
+
+    #[test]
+    fn test_frame_count_incomplete_frame_header() {
+        let mut blob = compress_framed(&CHUNKS[..1], Codec::Zlib).expect("Framed compression should work");
+        blob.push(0x80); // Continuation bit set, but no following byte
+
+        assert_eq!(frame_count(&blob), Err(CompressionError::InvalidVarint));
+    }
+
+    #[test]
+    fn test_framed_round_trip() {
+        for codec in Codec::ALL {
+            let blob = compress_framed(&CHUNKS, codec).expect("Framed compression should work");
+            let frames = decompress_framed(&blob, codec).expect("Framed decompression should work");
+            assert_eq!(frames, CHUNKS, "{} framed round trip should preserve every chunk", codec);
+        }
+    }
+
+    #[test]
+    fn test_decompress_framed_range_spans_frames() {
+        let joined = CHUNKS.concat();
+        for codec in Codec::ALL {
+            let blob = compress_framed(&CHUNKS, codec).expect("Framed compression should work");
+            for (start, len) in [(0, joined.len()), (CHUNKS[0].len() - 3, 10), (joined.len() - 4, 100)] {
+                let range = decompress_framed_range(&blob, codec, start, len).expect("Framed range should work");
+                let expected_end = (start + len).min(joined.len());
+                assert_eq!(range, joined.as_bytes()[start..expected_end], "{} framed range starting at {}", codec, start);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompress_framed_range_skips_frames() {
+        let mut blob = compress_framed(&CHUNKS, Codec::Zlib).expect("Framed compression should work");
+        // Corrupt the first frame's payload; a range in a later frame must not touch it
+        let (first_len, header_len) = decode_varint_rust(&blob[1..]).unwrap();
+        let last_byte = 1 + header_len + first_len as usize - 1;
+        blob[last_byte] ^= 0xFF;
+
+        let skip = CHUNKS[0].len() + CHUNKS[1].len();
+        assert_eq!(decompress_framed_range(&blob, Codec::Zlib, skip, CHUNKS[2].len()), Ok(CHUNKS[2].as_bytes().to_vec()));
+        assert!(decompress_framed(&blob, Codec::Zlib).is_err(), "A full decompress should hit the corrupted frame");
+    }
+}
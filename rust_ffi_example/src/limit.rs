@@ -0,0 +1,96 @@
+//! Decompression with a hard cap on peak memory, for untrusted input.
+
+use crate::bytes::decompress_append;
+use crate::codec::Codec;
+use crate::decode_varint_rust;
+use crate::error::CompressionError;
+#[cfg(feature = "zstd")]
+use crate::zstd_decompress_working_set;
+
+/// zlib's documented inflate memory use: the 32 KB window plus about 7 KB of state.
+const ZLIB_INFLATE_WORKING_SET: usize = (1 << 15) + 7 * 1024;
+
+/// Memory the codec allocates for itself during a one-shot decompression, beyond the
+/// input and output buffers.
+fn working_set(codec: Codec) -> usize {
+    match codec {
+        Codec::Zlib => ZLIB_INFLATE_WORKING_SET,
+        // LZ4_decompress_safe decodes straight into the output and allocates nothing
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => 0,
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => unsafe { zstd_decompress_working_set() as usize },
+    }
+}
+
+/// Decompresses `data`, failing before anything is allocated if the call could need more
+/// than `peak_limit` bytes.
+///
+/// The peak is budgeted from the varint header, which is read before any allocation. It
+/// counts everything resident at once: the input, the C library's output buffer
+/// (`original_len + 1`), the returned `Vec` the output is copied into, and the codec's
+/// working set. The C decoders never write past the size the header declares, so a
+/// payload that inflates to more than its header claims fails with `HeaderMismatch`
+/// instead of growing past the budget.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the original bytes.
+/// * `Err(CompressionError::ResourceLimitExceeded)` if the budget is over `peak_limit`.
+/// * `Err(CompressionError)` if the input is malformed or fails to decompress.
+pub fn decompress_with_peak_limit(data: &[u8], codec: Codec, peak_limit: usize) -> Result<Vec<u8>, CompressionError> {
+    let (original_len, _) = decode_varint_rust(data)?;
+    let output = usize::try_from(original_len).unwrap_or(usize::MAX);
+
+    let needed = data
+        .len()
+        .saturating_add(output.saturating_add(1))
+        .saturating_add(output)
+        .saturating_add(working_set(codec));
+    if needed > peak_limit {
+        return Err(CompressionError::ResourceLimitExceeded { needed, limit: peak_limit });
+    }
+
+    // Sized up front so copying the output out of the C buffer cannot reallocate
+    let mut out = Vec::with_capacity(output);
+    decompress_append(data, codec, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes::compress_rust_bytes;
+
+    #[test]
+    fn test_peak_limit_aborts_bomb() {
+        // 8 MB of zeros compresses to a few KB with every codec
+        let original = vec![0u8; 8 << 20];
+        let limit = 1 << 20;
+
+        for codec in Codec::ALL {
+            let bomb = compress_rust_bytes(&original, codec).unwrap();
+            assert!(bomb.len() < limit / 8, "{} blob should be small enough to look harmless", codec);
+
+            match decompress_with_peak_limit(&bomb, codec, limit) {
+                Err(CompressionError::ResourceLimitExceeded { needed, limit: reported }) => {
+                    assert!(needed > 2 * original.len(), "{} budget should count the C buffer and the copy", codec);
+                    assert_eq!(reported, limit);
+                }
+                other => panic!("{} should abort before decompressing, got {:?}", codec, other.map(|v| v.len())),
+            }
+
+            let generous = decompress_with_peak_limit(&bomb, codec, 32 << 20).expect("A generous limit should decompress");
+            assert!(generous == original, "{} output should match the original", codec);
+        }
+    }
+
+    #[test]
+    fn test_peak_limit_small_input_fits() {
+        let data = b"fits comfortably";
+        for codec in Codec::ALL {
+            let compressed = compress_rust_bytes(data, codec).unwrap();
+            assert_eq!(decompress_with_peak_limit(&compressed, codec, 1 << 20).as_deref(), Ok(&data[..]));
+        }
+        assert_eq!(decompress_with_peak_limit(&[], Codec::Zlib, 1 << 20), Err(CompressionError::EmptyInput));
+    }
+}
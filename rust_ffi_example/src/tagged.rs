@@ -0,0 +1,79 @@
+//! Self-describing single-blob container that records which codec produced it.
+//!
+//! Layout of a tagged blob:
+//!
+//! ```text
+//! [version][codec id][varint original length][compressed data]
+//! ```
+//!
+//! The version byte is shared with the framed format (see `FORMAT_VERSION`) and the
+//! codec id comes from `Codec::id`, so `decompress_tagged` needs no out-of-band hints.
+
+use crate::codec::Codec;
+use crate::error::CompressionError;
+use crate::version::{strip_version, FORMAT_VERSION};
+
+/// Compresses `s` with `codec` and prefixes the result with the format version and codec id.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` containing the tagged blob.
+/// * `Err(CompressionError)` if compression fails.
+pub fn compress_tagged(s: &str, codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    let payload = codec.compress(s)?;
+    let mut out = Vec::with_capacity(payload.len() + 2);
+    out.push(FORMAT_VERSION);
+    out.push(codec.id());
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+/// Decompresses a blob produced by `compress_tagged`, picking the codec from its tag.
+///
+/// The version byte is checked before anything else, so data written by a newer
+/// release fails with `UnsupportedVersion` rather than being misread.
+///
+/// # Returns
+/// * `Ok(String)` with the original string.
+/// * `Err(CompressionError)` if the version or codec tag is not supported, or the payload
+///   fails to decompress.
+pub fn decompress_tagged(data: &[u8]) -> Result<String, CompressionError> {
+    let rest = strip_version(data)?;
+    let (&tag, payload) = rest.split_first().ok_or(CompressionError::EmptyInput)?;
+    let codec = Codec::from_id(tag).ok_or(CompressionError::UnknownCodecTag(tag))?;
+    codec.decompress(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_round_trip_all_codecs() {
+        let original_data = "Tagged blobs remember their codec. ".repeat(10);
+        for codec in Codec::ALL {
+            let blob = compress_tagged(&original_data, codec).expect("Tagged compression should work");
+            assert_eq!(blob[0], FORMAT_VERSION);
+            assert_eq!(blob[1], codec.id());
+            assert_eq!(decompress_tagged(&blob), Ok(original_data.clone()), "{} tagged round trip failed", codec);
+        }
+    }
+
+    #[test]
+    fn test_tagged_future_version_rejected() {
+        let mut blob = compress_tagged("from the future", Codec::Zlib).expect("Tagged compression should work");
+        blob[0] = FORMAT_VERSION + 1;
+
+        assert_eq!(
+            decompress_tagged(&blob),
+            Err(CompressionError::UnsupportedVersion { found: FORMAT_VERSION + 1, max_supported: FORMAT_VERSION })
+        );
+    }
+
+    #[test]
+    fn test_tagged_unknown_codec() {
+        let mut blob = compress_tagged("unknown codec", Codec::Zlib).expect("Tagged compression should work");
+        blob[1] = 0xEE;
+        assert_eq!(decompress_tagged(&blob), Err(CompressionError::UnknownCodecTag(0xEE)));
+        assert_eq!(decompress_tagged(&[FORMAT_VERSION]), Err(CompressionError::EmptyInput));
+    }
+}
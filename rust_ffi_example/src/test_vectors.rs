@@ -0,0 +1,155 @@
+//! Canonical test inputs shared by the per-codec test modules.
+//!
+//! Add new cases here rather than in one codec's tests, so every codec is checked
+//! against the same data.
+
+/// Expands to the standard round-trip and error-handling suite for one codec.
+///
+/// Invoke it inside a `#[cfg(test)]` module, next to any codec-specific tests:
+///
+/// ```ignore
+/// codec_roundtrip_tests!(Codec::Zstd, compress_rust_string_zstd, decompress_rust_data_zstd);
+/// ```
+macro_rules! codec_roundtrip_tests {
+    ($codec:expr, $compress:path, $decompress:path) => {
+        #[test]
+        fn suite_empty_string_round_trip() {
+            let compressed = $compress("").expect("Empty string compression should work");
+            assert!(!compressed.is_empty(), "{} compressed empty string should still carry a header", $codec);
+            assert_eq!($decompress(&compressed).as_deref(), Ok(""), "{} empty string round trip", $codec);
+        }
+
+        #[test]
+        fn suite_round_trip() {
+            let original_data = "A standard round trip through the codec under test. It is long enough to compress.";
+            let compressed = $compress(original_data).expect("Compression should work");
+            assert_eq!($decompress(&compressed).as_deref(), Ok(original_data), "{} round trip", $codec);
+        }
+
+        #[test]
+        fn suite_unicode_round_trip() {
+            for &original_data in $crate::test_vectors::UNICODE_SAMPLES {
+                let compressed = $compress(original_data)
+                    .unwrap_or_else(|e| panic!("{} compression failed for '{}': {}", $codec, original_data, e));
+                assert_eq!($decompress(&compressed).as_deref(), Ok(original_data), "{} Unicode round trip", $codec);
+            }
+        }
+
+        #[test]
+        fn suite_repetitive_round_trip() {
+            for &original_data in $crate::test_vectors::REPETITIVE {
+                let compressed = $compress(original_data).expect("Compression of repetitive data should work");
+                assert!(compressed.len() < original_data.len(), "{} should shrink {:?}", $codec, original_data);
+                assert_eq!($decompress(&compressed).as_deref(), Ok(original_data), "{} repetitive round trip", $codec);
+            }
+
+            let original_data = "a".repeat(10000);
+            let compressed = $compress(&original_data).expect("Compression of repetitive data should work");
+            assert!(compressed.len() < original_data.len() / 10 + 10, "{} should compress repetitive data significantly", $codec);
+            assert_eq!($decompress(&compressed), Ok(original_data), "{} long repetitive round trip", $codec);
+        }
+
+        #[test]
+        fn suite_edge_cases_round_trip() {
+            for &original_data in $crate::test_vectors::EDGE_CASES {
+                let compressed = $compress(original_data).expect("Compression of edge cases should work");
+                assert_eq!($decompress(&compressed).as_deref(), Ok(original_data), "{} edge case round trip", $codec);
+            }
+        }
+
+        #[test]
+        fn suite_null_byte_rejected() {
+            assert_eq!($compress("hello\0world"), Err($crate::CompressionError::NullByte));
+        }
+
+        #[test]
+        fn suite_corrupted_header() {
+            let mut compressed = $compress("This is a test string for testing a corrupted header.").expect("Compression should work");
+            compressed[0] = 0xFF;
+            compressed[1] = 0xFF;
+            let result = $decompress(&compressed);
+            assert!(result.is_err(), "{} decompression with a corrupted header should fail, got {:?}", $codec, result);
+        }
+
+        #[test]
+        fn suite_zero_header_with_payload() {
+            // A varint header of 0 followed by a payload that decodes to real data
+            let valid = $compress("This payload is not empty").expect("Compression should work");
+            let (_, header_len) = $crate::decode_varint_rust(&valid).unwrap();
+            let mut inconsistent = $crate::encode_varint_rust(0).unwrap();
+            inconsistent.extend_from_slice(&valid[header_len..]);
+
+            assert_eq!($decompress(&inconsistent), Err($crate::CompressionError::HeaderMismatch($codec)),
+                "{} zero-length header with a non-empty payload should be a header mismatch", $codec);
+        }
+
+        #[test]
+        fn suite_empty_frame() {
+            // A varint header of 0 and no payload at all is the canonical empty frame
+            let frame = $crate::encode_varint_rust(0).unwrap();
+            assert_eq!(frame, $crate::EMPTY_FRAME);
+            assert_eq!($decompress(&frame), Ok(String::new()), "{} should accept the header-only empty frame", $codec);
+        }
+
+        #[test]
+        fn suite_invalid_data() {
+            assert_eq!($decompress(&[]), Err($crate::CompressionError::EmptyInput));
+            assert_eq!($decompress(&[0x05]), Err($crate::CompressionError::InputTooSmall($codec)));
+
+            let mut truncated_body = $crate::encode_varint_rust(10).unwrap();
+            truncated_body.push(0x01);
+            let cases: [&[u8]; 3] = [
+                &truncated_body,                  // 10 bytes promised, one byte of payload
+                &[0x12, 0x34, 0x56],              // garbage
+                &[0x78, 0x9c, 0xff, 0xff, 0xff],  // zlib-looking garbage
+            ];
+            for case in cases {
+                let result = $decompress(case);
+                assert!(result.is_err(), "{} decompression of {:02x?} should fail, got {:?}", $codec, case, result);
+            }
+        }
+    };
+}
+
+/// Non-ASCII text covering multi-byte UTF-8 sequences of every length.
+pub const UNICODE_SAMPLES: &[&str] = &[
+    "Hello, 世界!",
+    "🦀 Rust FFI 🦀",
+    "café naïve résumé",
+    "𝕳𝖊𝖑𝖑𝖔",
+    "Алло, мир!", // Cyrillic
+];
+
+/// Inputs dominated by repeated bytes or short repeated patterns.
+pub const REPETITIVE: &[&str] = &[
+    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+    "0101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101010101",
+    "Hello world! Hello world! Hello world! Hello world! Hello world! Hello world! Hello world! ",
+    "                                                                                                    ",
+];
+
+/// Boundary inputs: empty, single bytes, whitespace and punctuation.
+pub const EDGE_CASES: &[&str] = &["", "A", " ", "\n\r\t", "!@#$%^&*()", "\"'\\`", "\u{7f}", "\u{10ffff}"];
+
+/// Every shared vector, for tests that just need to round-trip all of them.
+pub fn all() -> impl Iterator<Item = &'static str> {
+    UNICODE_SAMPLES.iter().chain(REPETITIVE).chain(EDGE_CASES).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Codec;
+
+    #[test]
+    fn test_all_vectors_round_trip_every_codec() {
+        for codec in Codec::ALL {
+            for original_data in all() {
+                let compressed = codec
+                    .compress(original_data)
+                    .unwrap_or_else(|e| panic!("{} compression failed for {:?}: {}", codec, original_data, e));
+                assert_eq!(codec.decompress(&compressed).as_deref(), Ok(original_data), "{} round trip failed for {:?}", codec, original_data);
+            }
+        }
+    }
+}
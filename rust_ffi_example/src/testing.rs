@@ -0,0 +1,70 @@
+//! Helpers for integration tests that want to exercise the file I/O path end to end.
+//!
+//! Compiled for this crate's own tests and, for downstream crates, behind the
+//! `tempfile` feature:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! rust_ffi_example = { path = "...", features = ["tempfile"] }
+//! ```
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::codec::Codec;
+use crate::error::CompressionError;
+
+/// Compresses `s` to a temporary file, reads the file back and decompresses it.
+///
+/// The temporary file is created in the system temp directory and removed before
+/// this function returns, whether or not the round trip succeeded.
+///
+/// # Returns
+/// * `Ok(String)` with the decompressed contents of the file.
+/// * `Err(CompressionError)` if compression, file I/O or decompression fails.
+pub fn roundtrip_via_tempfile(s: &str, codec: Codec) -> Result<String, CompressionError> {
+    roundtrip_via_tempfile_in(std::env::temp_dir(), s, codec)
+}
+
+/// Same as [`roundtrip_via_tempfile`], but creates the temporary file inside `dir`.
+pub fn roundtrip_via_tempfile_in<P: AsRef<Path>>(dir: P, s: &str, codec: Codec) -> Result<String, CompressionError> {
+    let compressed = codec.compress(s)?;
+
+    // NamedTempFile deletes the file when dropped, including on the error paths below.
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(&compressed)?;
+    file.flush()?;
+
+    // Read back through the path rather than the open handle so the data really
+    // goes through the filesystem.
+    let read_back = fs::read(file.path())?;
+    codec.decompress(&read_back)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_via_tempfile_all_codecs() {
+        let original_data = "Round trip through a temporary file. ".repeat(20);
+        for codec in Codec::ALL {
+            let decompressed = roundtrip_via_tempfile(&original_data, codec)
+                .unwrap_or_else(|e| panic!("{} temp-file round trip failed: {}", codec, e));
+            assert_eq!(original_data, decompressed, "{} temp-file round trip should preserve the data", codec);
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_via_tempfile_cleans_up() {
+        let dir = tempfile::tempdir().expect("Creating a temp dir should work");
+
+        let decompressed = roundtrip_via_tempfile_in(dir.path(), "clean up after yourself", Codec::Zlib)
+            .expect("Temp-file round trip should work");
+        assert_eq!(decompressed, "clean up after yourself");
+
+        let leftovers: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+        assert!(leftovers.is_empty(), "Temp file should be removed after the round trip, found {:?}", leftovers);
+    }
+}
@@ -0,0 +1,422 @@
+//! Re-encoding compressed data with another codec without holding it all in memory.
+
+use std::io::{self, Read, Write};
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr::NonNull;
+use std::slice;
+
+use libc::c_ulong;
+
+use crate::bytes::{compress_rust_bytes, decompress_rust_data_bytes};
+use crate::codec::Codec;
+use crate::error::CompressionError;
+#[cfg(feature = "zstd")]
+use crate::writer::CompressWriter;
+#[cfg(feature = "zstd")]
+use crate::{zstd_dstream_create, zstd_dstream_decompress, zstd_dstream_free};
+use crate::{
+    decode_varint_rust, decompress_error, free_decompressed_data, zlib_dstream_create, zlib_dstream_decompress, zlib_dstream_free,
+    DecompressedData,
+};
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+/// Output produced per decoder call, so a small chunk of highly compressible input
+/// cannot expand into one huge allocation.
+const MAX_OUTPUT_PER_CALL: usize = 256 * 1024;
+/// Enough leading bytes for the longest varint header plus a payload magic number.
+const SNIFF_LEN: usize = 10 + 4;
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Most decompressed bytes `transcode_stream` collects for a zlib or LZ4 target, which
+/// need the whole input before they can write the length header. It matches the largest
+/// original length the one-shot decompressors accept, so nothing bigger could be read
+/// back anyway; transcode larger archives to zstd, which streams.
+pub const TRANSCODE_BUFFER_LIMIT: usize = 100 * 1024 * 1024;
+
+/// Source format read from the leading bytes.
+struct Detected {
+    codec: Codec,
+    /// Bytes of varint header before the payload; 0 for a headerless zstd frame.
+    header_len: usize,
+    /// Original length from the header, if there is one.
+    original_len: Option<u64>,
+}
+
+/// zlib streams start with a CMF/FLG pair: deflate method 8 and a checksum multiple of 31.
+fn is_zlib_header(payload: &[u8]) -> bool {
+    payload.len() >= 2 && payload[0] & 0x0F == 8 && (u16::from(payload[0]) << 8 | u16::from(payload[1])) % 31 == 0
+}
+
+fn detect(head: &[u8]) -> Result<Detected, CompressionError> {
+    if head.is_empty() {
+        return Err(CompressionError::EmptyInput);
+    }
+    // A frame from ZstdStreamCompressor / CompressWriter / compress_fd, no varint header
+    if head.starts_with(&ZSTD_MAGIC) {
+        let codec = Codec::from_name("zstd").ok_or(CompressionError::UnrecognizedFormat)?;
+        return Ok(Detected { codec, header_len: 0, original_len: None });
+    }
+
+    let (original_len, header_len) = decode_varint_rust(head)?;
+    let payload = &head[header_len..];
+    let codec = if payload.starts_with(&ZSTD_MAGIC) {
+        Codec::from_name("zstd")
+    } else if is_zlib_header(payload) {
+        Some(Codec::Zlib)
+    } else {
+        // LZ4 blocks have no magic number, so they are whatever is left
+        Codec::from_name("lz4")
+    };
+    let codec = codec.ok_or(CompressionError::UnrecognizedFormat)?;
+    Ok(Detected { codec, header_len, original_len: Some(original_len) })
+}
+
+/// Signature shared by `zlib_dstream_decompress` and `zstd_dstream_decompress`.
+type DecompressFn =
+    unsafe extern "C" fn(*mut c_void, *const c_char, c_ulong, c_ulong, *mut c_ulong, *mut c_int) -> DecompressedData;
+
+/// An incremental decoder from the C library.
+struct StreamDecoder {
+    stream: NonNull<c_void>,
+    codec: Codec,
+}
+
+impl StreamDecoder {
+    /// Returns `None` for codecs that can only be decoded whole (LZ4).
+    fn new(codec: Codec) -> Result<Option<Self>, CompressionError> {
+        let stream = unsafe {
+            match codec {
+                Codec::Zlib => zlib_dstream_create(),
+                #[cfg(feature = "lz4")]
+                Codec::Lz4 => return Ok(None),
+                #[cfg(feature = "zstd")]
+                Codec::Zstd => zstd_dstream_create(),
+            }
+        };
+        NonNull::new(stream)
+            .map(|stream| Some(StreamDecoder { stream, codec }))
+            .ok_or(CompressionError::DecompressionFailed(codec))
+    }
+
+    /// Returns the output produced, the input bytes consumed, and whether the stream ended.
+    fn decompress(&mut self, input: &[u8]) -> Result<(Vec<u8>, usize, bool), CompressionError> {
+        let mut consumed: c_ulong = 0;
+        let mut finished: c_int = 0;
+        let decompress_fn: DecompressFn = match self.codec {
+            Codec::Zlib => zlib_dstream_decompress,
+            #[cfg(feature = "lz4")]
+            Codec::Lz4 => unreachable!("LZ4 has no stream decoder"),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => zstd_dstream_decompress,
+        };
+        let decompressed_c_data = unsafe {
+            decompress_fn(
+                self.stream.as_ptr(),
+                input.as_ptr() as *const c_char,
+                input.len() as c_ulong,
+                MAX_OUTPUT_PER_CALL as c_ulong,
+                &mut consumed,
+                &mut finished,
+            )
+        };
+
+        if decompressed_c_data.buffer.is_null() {
+            return Err(decompress_error(&decompressed_c_data, self.codec));
+        }
+
+        let rust_vec = unsafe {
+            let slice = slice::from_raw_parts(decompressed_c_data.buffer as *const u8, decompressed_c_data.length as usize);
+            let vec = slice.to_vec();
+            free_decompressed_data(decompressed_c_data);
+            vec
+        };
+
+        Ok((rust_vec, consumed as usize, finished != 0))
+    }
+}
+
+impl Drop for StreamDecoder {
+    fn drop(&mut self) {
+        unsafe {
+            match self.codec {
+                Codec::Zlib => zlib_dstream_free(self.stream.as_ptr()),
+                #[cfg(feature = "lz4")]
+                Codec::Lz4 => {}
+                #[cfg(feature = "zstd")]
+                Codec::Zstd => zstd_dstream_free(self.stream.as_ptr()),
+            }
+        }
+    }
+}
+
+/// Where decompressed data goes: streamed into zstd, or collected for codecs that need
+/// the original length up front.
+enum Sink<'a, W: Write> {
+    #[cfg(feature = "zstd")]
+    Zstd(CompressWriter<&'a mut W>),
+    Buffered { codec: Codec, data: Vec<u8>, output: &'a mut W },
+}
+
+impl<'a, W: Write> Sink<'a, W> {
+    fn new(output: &'a mut W, to: Codec) -> Result<Self, CompressionError> {
+        #[cfg(feature = "zstd")]
+        if to == Codec::Zstd {
+            // Same level as compress_rust_string_zstd and compress_fd
+            return Ok(Sink::Zstd(CompressWriter::new(output, 1)?));
+        }
+        Ok(Sink::Buffered { codec: to, data: Vec::new(), output })
+    }
+
+    fn write(&mut self, decompressed: &[u8]) -> Result<(), CompressionError> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Sink::Zstd(writer) => writer.write_all(decompressed)?,
+            Sink::Buffered { data, .. } => {
+                let len = data.len() + decompressed.len();
+                if len > TRANSCODE_BUFFER_LIMIT {
+                    return Err(CompressionError::InputTooLarge { len, max: TRANSCODE_BUFFER_LIMIT });
+                }
+                data.extend_from_slice(decompressed);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), CompressionError> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Sink::Zstd(writer) => {
+                writer.finish()?;
+            }
+            Sink::Buffered { codec, data, output } => {
+                output.write_all(&compress_rust_bytes(&data, codec)?)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads into `buf`, retrying on `Interrupted`. Returns 0 at end of input.
+fn read_some<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match input.read(buf) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Reads compressed data from `input`, detects which codec produced it, and writes it
+/// to `output` re-encoded with `to`.
+///
+/// The source codec is detected from the leading bytes: a zstd frame magic number means
+/// a headerless frame from `ZstdStreamCompressor`, `CompressWriter` or `compress_fd`;
+/// otherwise the input is `[varint original length][payload]` and the payload's zlib
+/// header or zstd magic number gives the codec. LZ4 blocks carry no magic number, so
+/// any other payload is assumed to be LZ4.
+///
+/// zlib and zstd input is decoded incrementally, a chunk at a time. LZ4 blocks cannot
+/// be decoded in pieces and are read whole first.
+///
+/// The output matches `compress_fd`: for zstd it is a plain zstd frame written as it is
+/// produced, so memory use stays bounded; zlib and LZ4 store the original length in
+/// front of the payload, so their output is collected, up to `TRANSCODE_BUFFER_LIMIT`
+/// bytes, and written as one `[varint original length][payload]` blob at the end.
+///
+/// # Returns
+/// * `Ok(Codec)` with the detected source codec, once all output was written.
+/// * `Err(CompressionError::UnrecognizedFormat)` if the source codec is not compiled in.
+/// * `Err(CompressionError::InputTooLarge)` if a zlib or LZ4 target would need more than
+///   `TRANSCODE_BUFFER_LIMIT` bytes buffered. Nothing is written to `output` in that case.
+/// * `Err(CompressionError)` if the input is truncated, corrupt, followed by extra bytes
+///   or disagrees with its length header, or if reading or writing fails.
+pub fn transcode_stream<R: Read, W: Write>(input: &mut R, output: &mut W, to: Codec) -> Result<Codec, CompressionError> {
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    input.by_ref().take(SNIFF_LEN as u64).read_to_end(&mut head)?;
+    let Detected { codec: from, header_len, original_len } = detect(&head)?;
+
+    let mut sink = Sink::new(output, to)?;
+    let mut decoder = match StreamDecoder::new(from)? {
+        Some(decoder) => decoder,
+        None => {
+            let mut blob = head;
+            input.read_to_end(&mut blob)?;
+            sink.write(&decompress_rust_data_bytes(&blob, from)?)?;
+            sink.finish()?;
+            return Ok(from);
+        }
+    };
+
+    // Compressed bytes read but not yet consumed by the decoder
+    let mut pending = head.split_off(header_len);
+    let mut buf = vec![0u8; READ_CHUNK_SIZE];
+    let mut total: u64 = 0;
+    let mut finished = false;
+    loop {
+        let mut offset = 0;
+        while !finished {
+            let (out, consumed, done) = decoder.decompress(&pending[offset..])?;
+            offset += consumed;
+            total += out.len() as u64;
+            finished = done;
+            if original_len.is_some_and(|len| total > len) {
+                return Err(CompressionError::HeaderMismatch(from));
+            }
+            sink.write(&out)?;
+            // A full output buffer may leave more output waiting without any new input
+            if out.len() < MAX_OUTPUT_PER_CALL && (offset == pending.len() || consumed == 0) {
+                break;
+            }
+        }
+        pending.drain(..offset);
+
+        let n = read_some(input, &mut buf)?;
+        if finished && (n > 0 || !pending.is_empty()) {
+            // Bytes after the end of the compressed stream
+            return Err(CompressionError::CorruptData(from));
+        }
+        if n == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buf[..n]);
+    }
+
+    // The input ended before the compressed stream did
+    if !finished {
+        return Err(CompressionError::CorruptData(from));
+    }
+    if original_len.is_some_and(|len| total != len) {
+        return Err(CompressionError::HeaderMismatch(from));
+    }
+    sink.finish()?;
+    Ok(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Hands out at most `max_read` bytes per `read`, like a socket or pipe.
+    struct TrickleReader {
+        inner: Cursor<Vec<u8>>,
+        max_read: usize,
+    }
+
+    impl Read for TrickleReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.max_read);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    fn sample_data(lines: u32) -> Vec<u8> {
+        (0..lines).flat_map(|i| format!("{:08} archive entry {} of the migration\n", i, i % 113).into_bytes()).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_transcode_large_zlib_to_zstd() {
+        use crate::zstd_stream::decompress_zstd_stream;
+
+        let data = sample_data(100_000);
+        let zlib = compress_rust_bytes(&data, Codec::Zlib).unwrap();
+        assert!(zlib.len() > READ_CHUNK_SIZE, "Input should span several reads");
+
+        let mut input = TrickleReader { inner: Cursor::new(zlib), max_read: 4000 };
+        let mut output = Vec::new();
+        let from = transcode_stream(&mut input, &mut output, Codec::Zstd).expect("Transcoding should work");
+
+        assert_eq!(from, Codec::Zlib, "Source codec should be detected as zlib");
+        assert_eq!(decompress_zstd_stream(&output).map(|d| d == data), Ok(true), "Content should survive the transcode");
+    }
+
+    #[test]
+    fn test_transcode_large_input_to_zlib() {
+        let data = sample_data(100_000);
+        let zlib = compress_rust_bytes(&data, Codec::Zlib).unwrap();
+        assert!(zlib.len() > READ_CHUNK_SIZE, "Input should span several reads");
+
+        let mut input = TrickleReader { inner: Cursor::new(zlib), max_read: 4000 };
+        let mut output = Vec::new();
+        assert_eq!(transcode_stream(&mut input, &mut output, Codec::Zlib), Ok(Codec::Zlib));
+        assert_eq!(decompress_rust_data_bytes(&output, Codec::Zlib).map(|d| d == data), Ok(true));
+    }
+
+    #[test]
+    fn test_transcode_to_zlib_stops_at_buffer_limit() {
+        // A small blob that inflates to one byte more than a zlib target may buffer
+        let zlib = compress_rust_bytes(&vec![0u8; TRANSCODE_BUFFER_LIMIT + 1], Codec::Zlib).unwrap();
+        assert!(zlib.len() < TRANSCODE_BUFFER_LIMIT / 100);
+
+        let mut output = Vec::new();
+        match transcode_stream(&mut Cursor::new(zlib), &mut output, Codec::Zlib) {
+            Err(CompressionError::InputTooLarge { len, max }) => {
+                assert!(len > TRANSCODE_BUFFER_LIMIT);
+                assert_eq!(max, TRANSCODE_BUFFER_LIMIT);
+            }
+            other => panic!("Transcoding past the buffer limit should fail, got {:?}", other),
+        }
+        assert!(output.is_empty(), "Nothing should be written when the limit is hit");
+    }
+
+    #[test]
+    fn test_transcode_detects_every_codec() {
+        let data = sample_data(2_000);
+        for codec in Codec::ALL {
+            let compressed = compress_rust_bytes(&data, codec).unwrap();
+            let mut output = Vec::new();
+            let mut input = TrickleReader { inner: Cursor::new(compressed), max_read: 1000 };
+            let from = transcode_stream(&mut input, &mut output, Codec::Zlib).expect("Transcoding should work");
+            assert_eq!(from, codec, "{} input should be detected", codec);
+            assert_eq!(decompress_rust_data_bytes(&output, Codec::Zlib).map(|d| d == data), Ok(true));
+        }
+
+        #[cfg(feature = "zstd")]
+        {
+            let mut compressor = crate::ZstdStreamCompressor::new(3).unwrap();
+            let mut frame = compressor.write(&data).unwrap();
+            frame.extend(compressor.finish().unwrap());
+            let mut output = Vec::new();
+            assert_eq!(transcode_stream(&mut Cursor::new(frame), &mut output, Codec::Zlib), Ok(Codec::Zstd));
+            assert_eq!(decompress_rust_data_bytes(&output, Codec::Zlib).map(|d| d == data), Ok(true));
+        }
+    }
+
+    #[test]
+    fn test_transcode_output_larger_than_one_call() {
+        // A few KB of zlib that inflate to many times MAX_OUTPUT_PER_CALL
+        let data = vec![0u8; 16 * MAX_OUTPUT_PER_CALL + 123];
+        let zlib = compress_rust_bytes(&data, Codec::Zlib).unwrap();
+        assert!(zlib.len() < READ_CHUNK_SIZE);
+
+        let mut output = Vec::new();
+        transcode_stream(&mut Cursor::new(zlib), &mut output, Codec::Zlib).expect("Transcoding should work");
+        assert_eq!(decompress_rust_data_bytes(&output, Codec::Zlib).map(|d| d == data), Ok(true));
+    }
+
+    #[test]
+    fn test_transcode_rejects_damaged_input() {
+        let zlib = compress_rust_bytes(&sample_data(2_000), Codec::Zlib).unwrap();
+
+        let truncated = &zlib[..zlib.len() / 2];
+        let result = transcode_stream(&mut Cursor::new(truncated), &mut Vec::new(), Codec::Zlib);
+        assert_eq!(result, Err(CompressionError::CorruptData(Codec::Zlib)));
+
+        let mut trailing = zlib.clone();
+        trailing.extend_from_slice(b"extra");
+        let result = transcode_stream(&mut Cursor::new(trailing), &mut Vec::new(), Codec::Zlib);
+        assert_eq!(result, Err(CompressionError::CorruptData(Codec::Zlib)));
+
+        // A deflate block header with the reserved block type, right after the zlib header
+        let (_, header_len) = decode_varint_rust(&zlib).unwrap();
+        let mut garbled = zlib.clone();
+        garbled[header_len + 2] = 0xFF;
+        let result = transcode_stream(&mut Cursor::new(garbled), &mut Vec::new(), Codec::Zlib);
+        assert_eq!(result, Err(CompressionError::CorruptData(Codec::Zlib)));
+        assert!(result.unwrap_err().is_client_error());
+
+        assert_eq!(transcode_stream(&mut Cursor::new(Vec::new()), &mut Vec::new(), Codec::Zlib), Err(CompressionError::EmptyInput));
+    }
+}
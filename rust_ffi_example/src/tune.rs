@@ -0,0 +1,109 @@
+//! Picking a compression level for a dataset from a quick sweep over a sample.
+
+use std::time::{Duration, Instant};
+
+use crate::bytes::compress_rust_bytes_level;
+use crate::codec::Codec;
+
+/// Timing runs per level; the fastest run is kept to reduce scheduler noise.
+const RUNS_PER_LEVEL: usize = 3;
+
+/// What `find_best_level` should optimize for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Fastest compression, whatever the size.
+    Speed,
+    /// Most bytes saved per unit of compression time.
+    Balanced,
+    /// Smallest output, whatever the time.
+    Ratio,
+}
+
+/// Levels tried by the sweep, from fastest to strongest. Empty for codecs without levels.
+fn candidate_levels(codec: Codec) -> &'static [i32] {
+    match codec {
+        Codec::Zlib => &[1, 3, 6, 9],
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => &[],
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => &[-5, 1, 3, 9, 19],
+    }
+}
+
+struct Measurement {
+    level: i32,
+    size: usize,
+    time: Duration,
+}
+
+/// Compresses `sample` at a few representative levels and returns the one that best
+/// matches `target`.
+///
+/// The sample should look like the data the level will be used for; a few hundred KB is
+/// plenty. Every candidate level compresses the sample several times, so expect the
+/// sweep to take a few dozen times longer than a single compression at the strongest
+/// level. Timings are wall-clock, so `Speed` and `Balanced` can differ between runs
+/// when candidates are close.
+///
+/// * `Speed` returns the level with the lowest compression time.
+/// * `Balanced` returns the level that saves the most bytes per millisecond.
+/// * `Ratio` returns the level with the smallest output, preferring the lower level on ties.
+///
+/// Returns 0 for codecs without a level setting (LZ4).
+pub fn find_best_level(sample: &[u8], codec: Codec, target: Priority) -> i32 {
+    let measurements: Vec<Measurement> = candidate_levels(codec)
+        .iter()
+        .filter_map(|&level| {
+            let mut size = 0;
+            let mut time = Duration::MAX;
+            for _ in 0..RUNS_PER_LEVEL {
+                let start = Instant::now();
+                size = compress_rust_bytes_level(sample, codec, level).ok()?.len();
+                time = time.min(start.elapsed());
+            }
+            Some(Measurement { level, size, time })
+        })
+        .collect();
+
+    // Ties go to the lower level: min_by_key keeps the first minimum, and max_by keeps
+    // the last maximum, hence the rev()
+    let saved_per_ms = |m: &Measurement| sample.len().saturating_sub(m.size) as f64 / (m.time.as_secs_f64() * 1000.0).max(1e-6);
+    let best = match target {
+        Priority::Speed => measurements.iter().min_by_key(|m| m.time),
+        Priority::Ratio => measurements.iter().min_by_key(|m| m.size),
+        Priority::Balanced => measurements.iter().rev().max_by(|a, b| saved_per_ms(a).total_cmp(&saved_per_ms(b))),
+    };
+    best.map_or(0, |m| m.level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compressible_sample() -> Vec<u8> {
+        // Long-range repetition with local variation, so stronger levels find more matches
+        (0..4_000u32).flat_map(|i| format!("record {:05} status={} ", i % 600, ["ok", "retry", "fail"][(i % 7 % 3) as usize]).into_bytes()).collect()
+    }
+
+    #[test]
+    fn test_ratio_picks_higher_level_than_speed() {
+        let sample = compressible_sample();
+        for codec in Codec::ALL {
+            if codec.level_range().is_none() {
+                continue;
+            }
+            let speed = find_best_level(&sample, codec, Priority::Speed);
+            let ratio = find_best_level(&sample, codec, Priority::Ratio);
+            assert!(ratio > speed, "{} Ratio level {} should be above Speed level {}", codec, ratio, speed);
+
+            let balanced = find_best_level(&sample, codec, Priority::Balanced);
+            assert!(candidate_levels(codec).contains(&balanced));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn test_levelless_codec_returns_zero() {
+        assert_eq!(find_best_level(b"anything", Codec::Lz4, Priority::Ratio), 0);
+    }
+}
@@ -0,0 +1,22 @@
+//! Version byte shared by the self-describing container formats (framed and tagged blobs).
+//!
+//! The version is the first byte of the container. Readers check it before touching
+//! anything else, so a blob written by a newer release is rejected with an error that
+//! tells the user to upgrade instead of being misparsed.
+
+use crate::error::CompressionError;
+
+/// The newest container format version this release can read and the one it writes.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Checks the leading version byte and returns the rest of the container.
+pub(crate) fn strip_version(data: &[u8]) -> Result<&[u8], CompressionError> {
+    match data.split_first() {
+        None => Err(CompressionError::EmptyInput),
+        Some((&version, rest)) if (1..=FORMAT_VERSION).contains(&version) => Ok(rest),
+        Some((&found, _)) => Err(CompressionError::UnsupportedVersion {
+            found,
+            max_supported: FORMAT_VERSION,
+        }),
+    }
+}
@@ -0,0 +1,194 @@
+//! An `io::Write` adapter that zstd-compresses everything written through it.
+
+use std::io::{self, Write};
+
+use crate::error::CompressionError;
+use crate::zstd_stream::ZstdStreamCompressor;
+
+/// Compresses bytes written to it and forwards the zstd frame to `inner`.
+///
+/// The output is the same plain zstd frame `ZstdStreamCompressor` produces; read it back
+/// with `decompress_zstd_stream`. Call `finish` when done: dropping the writer without
+/// it leaves the frame unterminated.
+///
+/// # Non-blocking writers
+///
+/// Compressed output that `inner` has not accepted yet is kept in an internal buffer,
+/// and every `write`, `flush` and `finish` starts by handing that buffer to `inner`.
+/// Short writes are resumed from where they stopped and `Interrupted` is retried, so no
+/// byte is lost or written twice.
+///
+/// If `inner` returns `WouldBlock` (or any other error) while the buffer is being
+/// drained, the call fails with that error *before* consuming any new input: a failed
+/// `write` took none of its `buf`, so retry it with the same data once `inner` is
+/// writable again. `flush` and `try_finish` can be retried the same way; `finish`
+/// consumes the writer, so with a non-blocking `inner` call `try_finish` until it
+/// succeeds and then `finish`.
+pub struct CompressWriter<W: Write> {
+    inner: W,
+    compressor: ZstdStreamCompressor,
+    /// Compressed bytes not yet accepted by `inner`, from `pending_pos` on.
+    pending: Vec<u8>,
+    pending_pos: usize,
+    finished: bool,
+}
+
+impl<W: Write> CompressWriter<W> {
+    /// Wraps `inner`, compressing at the given zstd level (see `zstd_level_range`).
+    pub fn new(inner: W, level: i32) -> Result<Self, CompressionError> {
+        Ok(CompressWriter {
+            inner,
+            compressor: ZstdStreamCompressor::new(level)?,
+            pending: Vec::new(),
+            pending_pos: 0,
+            finished: false,
+        })
+    }
+
+    /// The wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Compressed bytes waiting for `inner` to accept them.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len() - self.pending_pos
+    }
+
+    /// Ends the frame and writes all remaining output, without giving up the writer.
+    ///
+    /// Safe to call again after an error; the frame is only ended once.
+    pub fn try_finish(&mut self) -> io::Result<()> {
+        if !self.finished {
+            let tail = self.compressor.end().map_err(io::Error::other)?;
+            self.pending.extend_from_slice(&tail);
+            self.finished = true;
+        }
+        self.drain_pending()?;
+        self.inner.flush()
+    }
+
+    /// Ends the frame, writes all remaining output and returns the wrapped writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.try_finish()?;
+        Ok(self.inner)
+    }
+
+    /// Hands every pending byte to `inner`, resuming after short writes.
+    fn drain_pending(&mut self) -> io::Result<()> {
+        while self.pending_pos < self.pending.len() {
+            match self.inner.write(&self.pending[self.pending_pos..]) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero, "inner writer accepted no compressed bytes")),
+                Ok(n) => self.pending_pos += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        self.pending.clear();
+        self.pending_pos = 0;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for CompressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.finished {
+            return Err(io::Error::other("write after the zstd frame was finished"));
+        }
+        // Earlier output first; on error nothing from `buf` has been consumed
+        self.drain_pending()?;
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let out = self.compressor.write(buf).map_err(io::Error::other)?;
+        self.pending.extend_from_slice(&out);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.finished {
+            let out = self.compressor.flush().map_err(io::Error::other)?;
+            self.pending.extend_from_slice(&out);
+        }
+        self.drain_pending()?;
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zstd_stream::decompress_zstd_stream;
+
+    /// Accepts at most `max_chunk` bytes per call and refuses every other call with `WouldBlock`.
+    struct TrickleWriter {
+        out: Vec<u8>,
+        max_chunk: usize,
+        block_next: bool,
+    }
+
+    impl Write for TrickleWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.block_next = !self.block_next;
+            if !self.block_next {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let n = buf.len().min(self.max_chunk);
+            self.out.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Retries `op` until the writer stops reporting `WouldBlock`.
+    fn retry<T>(mut op: impl FnMut() -> io::Result<T>) -> T {
+        loop {
+            match op() {
+                Ok(value) => return value,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(e) => panic!("Unexpected error: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_partial_writes_resume_without_loss() {
+        let input: Vec<u8> = (0..50_000u32).flat_map(|i| format!("event {} at t={}\n", i % 97, i).into_bytes()).collect();
+
+        // Reference: the same call sequence into a writer that never pushes back
+        let mut reference = CompressWriter::new(Vec::new(), 3).unwrap();
+        for chunk in input.chunks(4096) {
+            reference.write_all(chunk).unwrap();
+        }
+        reference.flush().unwrap();
+        let reference = reference.finish().unwrap();
+
+        for max_chunk in [1, 7, 1000] {
+            let trickle = TrickleWriter { out: Vec::new(), max_chunk, block_next: false };
+            let mut writer = CompressWriter::new(trickle, 3).unwrap();
+            for chunk in input.chunks(4096) {
+                let written = retry(|| writer.write(chunk));
+                assert_eq!(written, chunk.len(), "A successful write consumes the whole chunk");
+            }
+            retry(|| writer.flush());
+            assert_eq!(writer.pending_len(), 0, "A successful flush leaves nothing buffered");
+            retry(|| writer.try_finish());
+            let out = writer.finish().expect("Finishing after try_finish should not write again").out;
+
+            assert_eq!(out, reference, "max_chunk {}: output should match a writer without partial writes", max_chunk);
+            assert_eq!(decompress_zstd_stream(&out).as_deref(), Ok(&input[..]));
+        }
+    }
+
+    #[test]
+    fn test_write_after_finish_fails() {
+        let mut writer = CompressWriter::new(Vec::new(), 1).unwrap();
+        writer.write_all(b"done").unwrap();
+        writer.try_finish().unwrap();
+        assert!(writer.write(b"more").is_err());
+        assert_eq!(decompress_zstd_stream(&writer.finish().unwrap()).as_deref(), Ok(&b"done"[..]));
+    }
+}
@@ -0,0 +1,237 @@
+//! Incremental zstd compression for streams whose total length is not known up front.
+//!
+//! Unlike `compress_rust_string_zstd`, the output has no varint length header: it is a
+//! plain zstd frame, produced piece by piece as input arrives. Read it back with
+//! `decompress_zstd_stream`.
+
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr::NonNull;
+use std::slice;
+
+use libc::c_ulong;
+
+use crate::codec::Codec;
+use crate::error::CompressionError;
+use crate::{
+    decompress_data_zstd_stream, decompress_error, free_compressed_data, free_decompressed_data, zstd_level_range,
+    zstd_stream_compress, zstd_stream_create, zstd_stream_free, zstd_stream_set_target_block_size,
+};
+
+// `mode` values understood by `zstd_stream_compress`
+const MODE_CONTINUE: c_int = 0;
+const MODE_FLUSH: c_int = 1;
+const MODE_END: c_int = 2;
+
+/// A zstd compression stream.
+///
+/// `write` hands data to zstd, which may buffer it and return nothing until it has a
+/// full block; `flush` forces everything written so far out; `finish` ends the frame.
+pub struct ZstdStreamCompressor {
+    stream: NonNull<c_void>,
+}
+
+// The zstd context is only ever used through `&mut self`, so moving it between threads is fine.
+unsafe impl Send for ZstdStreamCompressor {}
+
+impl ZstdStreamCompressor {
+    /// Creates a stream at the given zstd level (see `zstd_level_range`).
+    pub fn new(level: i32) -> Result<Self, CompressionError> {
+        if !zstd_level_range().contains(&level) {
+            return Err(CompressionError::InvalidLevel { codec: Codec::Zstd, level });
+        }
+        let stream = unsafe { zstd_stream_create(level as c_int) };
+        NonNull::new(stream)
+            .map(|stream| ZstdStreamCompressor { stream })
+            .ok_or(CompressionError::CompressionFailed(Codec::Zstd))
+    }
+
+    /// Bounds each compressed block to roughly `bytes` (zstd's `ZSTD_c_targetCBlockSize`).
+    ///
+    /// Smaller blocks let a streaming reader decode and act on data sooner, at the cost
+    /// of a slightly worse ratio. zstd accepts 64 bytes up to its 128 KiB block size;
+    /// 0 restores the default of no target.
+    pub fn with_target_block_size(self, bytes: usize) -> Result<Self, CompressionError> {
+        let res = unsafe { zstd_stream_set_target_block_size(self.stream.as_ptr(), bytes as c_ulong) };
+        if res != 0 {
+            return Err(CompressionError::InvalidBlockSize(bytes));
+        }
+        Ok(self)
+    }
+
+    /// Compresses `data`, returning any output zstd is ready to emit (possibly none).
+    pub fn write(&mut self, data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        self.compress(data, MODE_CONTINUE)
+    }
+
+    /// Emits all data written so far as complete blocks, without ending the frame.
+    pub fn flush(&mut self) -> Result<Vec<u8>, CompressionError> {
+        self.compress(&[], MODE_FLUSH)
+    }
+
+    /// Ends the frame and returns the remaining output.
+    pub fn finish(mut self) -> Result<Vec<u8>, CompressionError> {
+        self.end()
+    }
+
+    /// Same as `finish`, for owners that cannot give up the compressor. Anything written
+    /// afterwards starts a new frame.
+    pub(crate) fn end(&mut self) -> Result<Vec<u8>, CompressionError> {
+        self.compress(&[], MODE_END)
+    }
+
+    fn compress(&mut self, data: &[u8], mode: c_int) -> Result<Vec<u8>, CompressionError> {
+        let compressed_c_data = unsafe {
+            zstd_stream_compress(self.stream.as_ptr(), data.as_ptr() as *const c_char, data.len() as c_ulong, mode)
+        };
+
+        if compressed_c_data.buffer.is_null() {
+            return Err(CompressionError::CompressionFailed(Codec::Zstd));
+        }
+
+        let rust_vec = unsafe {
+            let slice = slice::from_raw_parts(compressed_c_data.buffer as *const u8, compressed_c_data.length as usize);
+            let vec = slice.to_vec();
+            free_compressed_data(compressed_c_data);
+            vec
+        };
+
+        Ok(rust_vec)
+    }
+}
+
+impl Drop for ZstdStreamCompressor {
+    fn drop(&mut self) {
+        unsafe { zstd_stream_free(self.stream.as_ptr()) }
+    }
+}
+
+/// Decompresses a complete frame produced by `ZstdStreamCompressor`.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` with the original bytes.
+/// * `Err(CompressionError)` if the frame is malformed, truncated or followed by extra bytes.
+pub fn decompress_zstd_stream(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    if data.is_empty() {
+        return Err(CompressionError::EmptyInput);
+    }
+
+    let decompressed_c_data = unsafe { decompress_data_zstd_stream(data.as_ptr() as *const c_char, data.len() as c_ulong) };
+
+    if decompressed_c_data.buffer.is_null() {
+        return Err(decompress_error(&decompressed_c_data, Codec::Zstd));
+    }
+
+    let rust_vec = unsafe {
+        let slice = slice::from_raw_parts(decompressed_c_data.buffer as *const u8, decompressed_c_data.length as usize);
+        let vec = slice.to_vec();
+        free_decompressed_data(decompressed_c_data);
+        vec
+    };
+
+    Ok(rust_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compressed sizes of the blocks in a single zstd frame, from the block headers.
+    fn block_sizes(frame: &[u8]) -> Vec<usize> {
+        assert_eq!(frame[..4], [0x28, 0xB5, 0x2F, 0xFD], "Not a zstd frame");
+        let descriptor = frame[4];
+        let single_segment = descriptor & 0x20 != 0;
+        let dict_id_len = [0, 1, 2, 4][(descriptor & 0x03) as usize];
+        let content_size_len = match descriptor >> 6 {
+            0 => single_segment as usize,
+            1 => 2,
+            2 => 4,
+            _ => 8,
+        };
+        let mut pos = 5 + usize::from(!single_segment) + dict_id_len + content_size_len;
+
+        let mut sizes = Vec::new();
+        loop {
+            let header = u32::from_le_bytes([frame[pos], frame[pos + 1], frame[pos + 2], 0]);
+            let (last, block_type, size) = (header & 1 == 1, (header >> 1) & 3, (header >> 3) as usize);
+            // RLE blocks store a single byte whatever their decoded size
+            let stored = if block_type == 1 { 1 } else { size };
+            sizes.push(stored);
+            pos += 3 + stored;
+            if last {
+                return sizes;
+            }
+        }
+    }
+
+    fn log_lines(count: usize) -> Vec<u8> {
+        (0..count)
+            .flat_map(|i| format!("ts={} level=INFO request_id={:08x} latency_ms={}\n", 1_700_000_000 + i, i * 2654435761 % 0xFFFF_FFFF, i % 97).into_bytes())
+            .collect()
+    }
+
+    fn compress_stream(compressor: ZstdStreamCompressor, data: &[u8]) -> Vec<u8> {
+        let mut compressor = compressor;
+        let mut out = Vec::new();
+        for chunk in data.chunks(4096) {
+            out.extend(compressor.write(chunk).expect("Stream write should work"));
+        }
+        out.extend(compressor.finish().expect("Stream finish should work"));
+        out
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let data = log_lines(2000);
+        let frame = compress_stream(ZstdStreamCompressor::new(3).unwrap(), &data);
+        assert_eq!(decompress_zstd_stream(&frame), Ok(data));
+    }
+
+    #[test]
+    fn test_target_block_size_emits_smaller_blocks() {
+        let data = log_lines(20_000);
+
+        let default_frame = compress_stream(ZstdStreamCompressor::new(3).unwrap(), &data);
+        let small_frame = compress_stream(ZstdStreamCompressor::new(3).unwrap().with_target_block_size(1024).unwrap(), &data);
+
+        let default_blocks = block_sizes(&default_frame);
+        let small_blocks = block_sizes(&small_frame);
+        assert!(small_blocks.len() > default_blocks.len() * 4, "Expected many more blocks: {} vs {}", small_blocks.len(), default_blocks.len());
+        let largest = small_blocks.iter().copied().max().unwrap();
+        assert!(largest < default_blocks.iter().copied().max().unwrap(), "Blocks should be smaller with a target size");
+
+        assert_eq!(decompress_zstd_stream(&small_frame), Ok(data));
+    }
+
+    #[test]
+    fn test_flush_emits_pending_data() {
+        let mut compressor = ZstdStreamCompressor::new(3).unwrap().with_target_block_size(256).unwrap();
+        let mut frame = compressor.write(b"first message\n").unwrap();
+        assert!(frame.len() <= 6, "zstd should buffer a tiny write, got {} bytes", frame.len());
+        frame.extend(compressor.flush().unwrap());
+        frame.extend(compressor.write(b"second message\n").unwrap());
+        frame.extend(compressor.finish().unwrap());
+        assert_eq!(decompress_zstd_stream(&frame).as_deref(), Ok(&b"first message\nsecond message\n"[..]));
+    }
+
+    #[test]
+    fn test_invalid_settings_rejected() {
+        assert!(matches!(ZstdStreamCompressor::new(1000), Err(CompressionError::InvalidLevel { level: 1000, .. })));
+        assert!(matches!(ZstdStreamCompressor::new(3).unwrap().with_target_block_size(1 << 20), Err(CompressionError::InvalidBlockSize(_))));
+        assert_eq!(decompress_zstd_stream(&[0x28, 0xB5, 0x2F, 0xFD, 0x00]), Err(CompressionError::CorruptData(Codec::Zstd)));
+
+        // Truncated, garbled, and followed by trailing bytes: all faults in the input
+        let mut compressor = ZstdStreamCompressor::new(3).unwrap();
+        let mut frame = compressor.write(&b"a frame to damage ".repeat(50)).unwrap();
+        frame.extend(compressor.finish().unwrap());
+        let mut garbled = frame.clone();
+        let middle = garbled.len() / 2;
+        garbled[middle..].iter_mut().for_each(|byte| *byte ^= 0x5A);
+        let mut trailing = frame.clone();
+        trailing.extend_from_slice(b"extra");
+        for damaged in [&frame[..frame.len() - 3], &garbled[..], &trailing[..]] {
+            let error = decompress_zstd_stream(damaged).unwrap_err();
+            assert_eq!(error, CompressionError::CorruptData(Codec::Zstd));
+            assert!(error.is_client_error());
+        }
+    }
+}
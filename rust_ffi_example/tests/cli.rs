@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use rust_ffi_example::Codec;
+
+/// Runs the CLI binary inside `dir`, so its fixed output file names land there.
+fn run_cli(dir: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_compression_cli"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run compression_cli");
+    assert!(
+        output.status.success(),
+        "compression_cli {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn test_cli_compress_names_output_by_codec() {
+    let original = "CLI round trip through every codec. CLI round trip through every codec.";
+
+    for codec in Codec::ALL {
+        let dir = tempfile::tempdir().unwrap();
+        run_cli(dir.path(), &["compress", "--algo", codec.name(), original]);
+
+        let expected_file = dir.path().join(format!("compressed_output.{}", codec.file_extension()));
+        assert!(expected_file.exists(), "{} compress should write {}", codec, expected_file.display());
+        assert!(!dir.path().join("compressed_output.bin").exists(), "The codec-less .bin name should no longer be used");
+
+        let compressed = fs::read(&expected_file).unwrap();
+        assert_eq!(codec.decompress(&compressed).unwrap(), original, "{} output file should hold {} data", codec, codec);
+    }
+}
+
+#[test]
+fn test_cli_decompress_infers_codec_from_extension() {
+    let original = "Decompress picks the codec from the file name.";
+
+    for codec in Codec::ALL {
+        let dir = tempfile::tempdir().unwrap();
+        let file_name = format!("compressed_output.{}", codec.file_extension());
+        run_cli(dir.path(), &["compress", "--algo", codec.name(), original]);
+
+        // No --algo: the codec must come from the extension
+        let output = run_cli(dir.path(), &["decompress", &file_name]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(&format!("Algorithm: {}", codec.name())), "{} should be inferred, got:\n{}", codec, stdout);
+
+        let decompressed = fs::read_to_string(dir.path().join("decompressed_output.txt")).unwrap();
+        assert_eq!(decompressed, original, "{} CLI round trip should preserve the data", codec);
+    }
+}
+
+#[test]
+#[cfg(feature = "lz4")]
+fn test_cli_explicit_algo_overrides_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    run_cli(dir.path(), &["compress", "--algo", "lz4", "renamed file"]);
+    fs::rename(dir.path().join("compressed_output.lz4"), dir.path().join("data.bin")).unwrap();
+
+    run_cli(dir.path(), &["decompress", "--algo", "lz4", "data.bin"]);
+    let decompressed = fs::read_to_string(dir.path().join("decompressed_output.txt")).unwrap();
+    assert_eq!(decompressed, "renamed file");
+}
+
+#[test]
+fn test_cli_compress_level_changes_output_size() {
+    let original = "Level nine should squeeze this harder than level one. 0123456789 ".repeat(200);
+
+    for (codec, low, high) in [
+        (Codec::Zlib, "1", "9"),
+        #[cfg(feature = "zstd")]
+        (Codec::Zstd, "-5", "19"),
+    ] {
+        let mut sizes = Vec::new();
+        for level in [low, high] {
+            let dir = tempfile::tempdir().unwrap();
+            let output = run_cli(dir.path(), &["compress", "--algo", codec.name(), "--level", level, &original]);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(stdout.contains(&format!("Level: {}", level)), "Summary should show the level, got:\n{}", stdout);
+
+            let compressed = fs::read(dir.path().join(format!("compressed_output.{}", codec.file_extension()))).unwrap();
+            assert_eq!(codec.decompress(&compressed).unwrap(), original);
+            sizes.push(compressed.len());
+        }
+        assert!(sizes[1] < sizes[0], "{} level {} should be smaller than level {}: {:?}", codec, high, low, sizes);
+    }
+}
+
+#[test]
+fn test_cli_rejects_invalid_level() {
+    let dir = tempfile::tempdir().unwrap();
+    for args in [
+        ["compress", "--algo", "zlib", "--level", "10", "text"],
+        ["compress", "--algo", "lz4", "--level", "1", "text"],
+        ["compress", "--algo", "zstd", "--level", "fast", "text"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_compression_cli"))
+            .current_dir(dir.path())
+            .args(args)
+            .output()
+            .expect("Failed to run compression_cli");
+        assert!(!output.status.success(), "{:?} should be rejected", args);
+    }
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0, "Rejected runs should not write output");
+}
+
+#[test]
+fn test_cli_binary_round_trip_with_null_bytes() {
+    let original: Vec<u8> = (0..4096u32).map(|i| if i % 7 == 0 { 0 } else { (i * 31 % 256) as u8 }).chain([0xFF, 0xFE, 0x00]).collect();
+
+    for codec in Codec::ALL {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("input.bin"), &original).unwrap();
+
+        run_cli(dir.path(), &["compress", "--binary", "--algo", codec.name(), "input.bin"]);
+        let file_name = format!("compressed_output.{}", codec.file_extension());
+        let output = run_cli(dir.path(), &["decompress", "--binary", &file_name]);
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("Decompressed data:"), "Binary data should not be printed");
+
+        let decompressed = fs::read(dir.path().join("decompressed_output.bin")).unwrap();
+        assert_eq!(decompressed, original, "{} binary CLI round trip should preserve every byte", codec);
+    }
+}
+
+#[test]
+fn test_cli_binary_with_level() {
+    // The level test's text with null and non-UTF-8 bytes mixed in
+    let original: Vec<u8> = "Level nine should squeeze this harder than level one. 0123456789 "
+        .repeat(200)
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| match i % 97 {
+            0 => 0x00,
+            1 => 0xFF,
+            _ => b,
+        })
+        .collect();
+
+    for (codec, low, high) in [
+        (Codec::Zlib, "1", "9"),
+        #[cfg(feature = "zstd")]
+        (Codec::Zstd, "-5", "19"),
+    ] {
+        let mut sizes = Vec::new();
+        for level in [low, high] {
+            let dir = tempfile::tempdir().unwrap();
+            fs::write(dir.path().join("input.bin"), &original).unwrap();
+
+            let output = run_cli(dir.path(), &["compress", "--binary", "--algo", codec.name(), "--level", level, "input.bin"]);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(stdout.contains(&format!("Level: {}", level)), "Summary should show the level, got:\n{}", stdout);
+
+            let file_name = format!("compressed_output.{}", codec.file_extension());
+            sizes.push(fs::metadata(dir.path().join(&file_name)).unwrap().len());
+            run_cli(dir.path(), &["decompress", "--binary", &file_name]);
+            let decompressed = fs::read(dir.path().join("decompressed_output.bin")).unwrap();
+            assert_eq!(decompressed, original, "{} level {} binary round trip should preserve every byte", codec, level);
+        }
+        assert!(sizes[1] < sizes[0], "{} level {} should be smaller than level {}: {:?}", codec, high, low, sizes);
+    }
+}
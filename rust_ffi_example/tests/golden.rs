@@ -0,0 +1,73 @@
+//! Golden-output tests: compressed bytes must not drift across platforms or builds.
+//!
+//! Content-addressed storage depends on identical input producing identical output.
+//! Each codec's output for a fixed set of inputs is committed under `tests/golden/`.
+//! A library upgrade that legitimately changes the output must regenerate the files
+//! on purpose and call it out in review:
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --test golden
+//! ```
+
+use std::fs;
+use std::path::PathBuf;
+
+use rust_ffi_example::Codec;
+
+/// Fixed inputs; never change an existing entry, only add new ones.
+const INPUTS: [(&str, &str); 5] = [
+    ("empty", ""),
+    ("short", "hello"),
+    ("sentence", "The quick brown fox jumps over the lazy dog."),
+    ("unicode", "Hello, 世界! 🦀 café naïve résumé"),
+    ("repetitive", "golden golden golden golden golden golden golden golden golden golden golden golden "),
+];
+
+fn golden_path(codec: Codec, name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.{}", name, codec.file_extension()))
+}
+
+#[test]
+fn test_compressed_output_matches_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut drifted = Vec::new();
+
+    for codec in Codec::ALL {
+        for (name, input) in INPUTS {
+            let path = golden_path(codec, name);
+            let compressed = codec.compress(input).expect("Compression should work");
+
+            if update {
+                fs::write(&path, &compressed).expect("Writing golden file failed");
+                continue;
+            }
+
+            let golden = fs::read(&path).unwrap_or_else(|e| panic!("Missing golden file {}: {}", path.display(), e));
+            if compressed != golden {
+                drifted.push(path.display().to_string());
+            }
+            assert_eq!(codec.decompress(&golden).as_deref(), Ok(input), "Golden file {} should still decompress", path.display());
+        }
+    }
+
+    assert!(
+        drifted.is_empty(),
+        "Compressed output drifted from the golden files: {:?}\nIf this is an intentional library upgrade, rerun with UPDATE_GOLDEN=1 and commit the new files.",
+        drifted
+    );
+}
+
+#[test]
+fn test_compressed_output_is_repeatable() {
+    // Catches nondeterminism within one process (e.g. multithreaded compression) too
+    for codec in Codec::ALL {
+        for (_, input) in INPUTS {
+            let first = codec.compress(input).unwrap();
+            for _ in 0..5 {
+                assert_eq!(codec.compress(input).unwrap(), first, "{} output should be deterministic", codec);
+            }
+        }
+    }
+}